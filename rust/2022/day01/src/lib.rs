@@ -1,49 +1,119 @@
-pub mod input;
+use std::{error, fmt};
+use parsing::{error_at, Token};
+use rand::Rng;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::debug;
 
-/// Response to the first part
-pub fn highest_group_calories(calory_groups: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut highest_group_calories = 0;
-    let mut current_group_calories = 0;
-    for line in calory_groups {
-        let line = line.as_ref().trim();
-        if line.is_empty() {
-            if current_group_calories > highest_group_calories {
-                highest_group_calories = current_group_calories;
-            }
-            current_group_calories = 0;
-        } else {
-            current_group_calories += line.parse::<usize>().expect("Malformed file. Expected only number and empty lines");
+/// Errors found while parsing the calorie groups. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    InvalidCalories { line: usize, column: usize, text: String },
+    TooManyCalories { line: usize, column: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCalories { line, column, text } =>
+                write!(f, "line {}, column {}: expected a number of calories or a blank line, found `{}`", line, column, text),
+            ParseError::TooManyCalories { line, column, text } =>
+                write!(f, "line {}, column {}: group calories overflow when adding `{}`", line, column, text),
         }
     }
-    if current_group_calories > highest_group_calories {
-        highest_group_calories = current_group_calories;
+}
+
+impl error::Error for ParseError {}
+
+/// Total calories carried by each elf, in the order the groups appear in the input
+#[derive(PartialEq, Debug)]
+pub struct CalorieCounting {
+    group_calories: Vec<usize>,
+}
+
+impl CalorieCounting {
+    /// Sums every group of numbers. Groups are separated by blank lines.
+    pub fn from_lines(calory_groups: impl Iterator<Item=impl AsRef<str>>) -> Result<Self, ParseError> {
+        let calory_groups: Vec<_> = calory_groups.collect();
+        let group_calories = parsing::sections(parsing::lines(&calory_groups))
+            .into_iter()
+            .map(|group| group.into_iter().map(Token::trim).try_fold(0usize, |sum, line| {
+                let calories = line.integer::<usize>().map_err(error_at!(ParseError::InvalidCalories))?;
+                sum.checked_add(calories).ok_or_else(|| error_at!(ParseError::TooManyCalories)(line))
+            }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { group_calories })
+    }
+
+    fn highest_group_calories(&self) -> usize {
+        self.group_calories.iter().copied().max().unwrap_or(0)
+    }
+
+    /// None when the n groups add up to more than usize::MAX, even though each group fits
+    fn top_n_highest_group_calories(&self, n: usize) -> Option<usize> {
+        let mut sum_calory_groups = self.group_calories.clone();
+        sum_calory_groups.sort_unstable();
+
+        let start_index = sum_calory_groups.len().saturating_sub(n);
+
+        sum_calory_groups
+            .get(start_index..sum_calory_groups.len())
+            .expect("Range is always valid: at least 0 and at most len()")
+            .iter()
+            .try_fold(0, |sum: usize, calories| {
+                debug!(calories, sum, "adding group");
+                sum.checked_add(*calories)
+            })
+    }
+}
+
+impl Solution for CalorieCounting {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines())?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(self.highest_group_calories().into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        let calories = self.top_n_highest_group_calories(3).ok_or("the top 3 groups' calories overflow")?;
+        Ok(calories.into())
     }
-    highest_group_calories
 }
 
-/// Response to the second part
-pub fn top_n_highest_group_calories(calory_groups: impl Iterator<Item=impl AsRef<str>>, n: usize) -> usize {
-    let mut current_group_calories = 0;
-    let mut sum_calory_groups: Vec<usize> = vec![];
-    for line in calory_groups {
-        let line = line.as_ref().trim();
-        if line.is_empty() {
-            sum_calory_groups.push(current_group_calories);
-            current_group_calories = 0;
-        } else {
-            current_group_calories += line.parse::<usize>().expect("Malformed file. Expected only number and empty lines");
+/// Renders each group as a single food item holding the group's total
+impl fmt::Display for CalorieCounting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, calories) in self.group_calories.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", calories)?;
         }
+        Ok(())
+    }
+}
+
+impl Generator for CalorieCounting {
+    /// size groups of 1 to 8 food items, each holding 1,000 to 70,000 calories
+    fn generate(random: &mut Random, size: usize) -> String {
+        (0..size)
+            .map(|_| (0..random.random_range(1..=8))
+                .map(|_| format!("{}\n", random.random_range(1_000..=70_000)))
+                .collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
     }
-    sum_calory_groups.push(current_group_calories);
-    sum_calory_groups.sort_unstable();
+}
 
-    let start_index = sum_calory_groups.len().checked_sub(n).unwrap_or(0);
+/// Response to the first part
+pub fn highest_group_calories(calory_groups: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    Ok(CalorieCounting::from_lines(calory_groups)?.highest_group_calories())
+}
 
-    sum_calory_groups
-        .get(start_index..sum_calory_groups.len())
-        .expect("Range is always valid: at least 0 and at most len()")
-        .iter()
-        .sum()
+/// Response to the second part, None when the sum overflows
+pub fn top_n_highest_group_calories(calory_groups: impl Iterator<Item=impl AsRef<str>>, n: usize) -> Result<Option<usize>, ParseError> {
+    Ok(CalorieCounting::from_lines(calory_groups)?.top_n_highest_group_calories(n))
 }
 
 #[cfg(test)]
@@ -52,7 +122,7 @@ mod tests {
 
     #[test]
     fn test_highest_calories_no_groups() {
-        assert_eq!(highest_group_calories("".lines()), 0);
+        assert_eq!(highest_group_calories("".lines()), Ok(0));
     }
 
     #[test]
@@ -62,7 +132,7 @@ mod tests {
 2000
 3000
 
-".lines()), 6000);
+".lines()), Ok(6000));
     }
 
     #[test]
@@ -74,7 +144,7 @@ mod tests {
 
 4000
 5000
-6000".lines()), 15000);
+6000".lines()), Ok(15000));
     }
 
     #[test]
@@ -90,12 +160,12 @@ mod tests {
 
 7000
 8000
-9000".lines()), 24000);
+9000".lines()), Ok(24000));
     }
 
     #[test]
     fn test_top_three_highest_calories_no_groups() {
-        assert_eq!(top_n_highest_group_calories("".lines(), 3), 0);
+        assert_eq!(top_n_highest_group_calories("".lines(), 3), Ok(Some(0)));
     }
 
     #[test]
@@ -105,7 +175,7 @@ mod tests {
 2000
 3000
 
-".lines(), 3), 6000);
+".lines(), 3), Ok(Some(6000)));
     }
 
     #[test]
@@ -117,7 +187,7 @@ mod tests {
 
 4000
 5000
-6000".lines(), 3), 21000);
+6000".lines(), 3), Ok(Some(21000)));
     }
 
     #[test]
@@ -133,7 +203,7 @@ mod tests {
 
 7000
 8000
-9000".lines(), 3), 45000);
+9000".lines(), 3), Ok(Some(45000)));
     }
 
     #[test]
@@ -148,6 +218,63 @@ mod tests {
 
 4000
 
-5000".lines(), 3), 11000);
+5000".lines(), 3), Ok(Some(11000)));
+    }
+
+    #[test]
+    fn test_calorie_counting_solution() {
+        let calorie_counting = CalorieCounting::parse("\
+1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000").unwrap();
+        assert_eq!(calorie_counting.part1().unwrap(), Answer::Number(24000));
+        assert_eq!(calorie_counting.part2().unwrap(), Answer::Number(45000));
+    }
+
+    #[test]
+    fn test_top_three_highest_calories_overflow() {
+        let calorie_counting = CalorieCounting::parse("9223372036854775807\n\n9223372036854775807").unwrap();
+        assert_eq!(calorie_counting.part2().unwrap(), Answer::Number(18446744073709551614));
+        assert_eq!(top_n_highest_group_calories(format!("{}\n\n1", usize::MAX).lines(), 3), Ok(None));
+        let calorie_counting = CalorieCounting::parse(&format!("{}\n\n1", usize::MAX)).unwrap();
+        assert!(calorie_counting.part2().is_err());
+    }
+
+    #[test]
+    fn test_highest_calories_invalid_calories() {
+        assert_eq!(highest_group_calories("\
+1000
+
+ 20a0".lines()), Err(ParseError::InvalidCalories { line: 3, column: 2, text: "20a0".to_string() }));
+    }
+
+    #[test]
+    fn test_highest_calories_too_many_calories() {
+        assert_eq!(highest_group_calories(["1", &usize::MAX.to_string()].iter()),
+                   Err(ParseError::TooManyCalories { line: 2, column: 1, text: usize::MAX.to_string() }));
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = CalorieCounting::generate(&mut solution::random(seed), 10);
+            assert_eq!(input.split("\n\n").count(), 10);
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<CalorieCounting>();
     }
 }
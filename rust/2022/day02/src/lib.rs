@@ -1,21 +1,50 @@
-pub mod input;
+use std::{error, fmt};
+use parsing::{error_at, Token};
+use rand::Rng;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::debug;
 
+/// Errors found while parsing the strategy guide. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    MissingSeparator { line: usize, column: usize, text: String },
+    InvalidOpponentShape { line: usize, column: usize, text: String },
+    InvalidMyShape { line: usize, column: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingSeparator { line, column, text } =>
+                write!(f, "line {}, column {}: expected two columns separated by a space, found `{}`", line, column, text),
+            ParseError::InvalidOpponentShape { line, column, text } =>
+                write!(f, "line {}, column {}: expected opponent shape A, B or C, found `{}`", line, column, text),
+            ParseError::InvalidMyShape { line, column, text } =>
+                write!(f, "line {}, column {}: expected X, Y or Z, found `{}`", line, column, text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[allow(clippy::upper_case_acronyms)]
 enum PlayOutcome{
     WIN = 6,
     DRAW = 3,
     LOSE = 0,
 }
 
-#[derive(PartialEq, Copy, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 enum RPS { ROCK, PAPER, SCISORS }
 
 impl RPS {
-    fn new(shape: char) -> Self {
+    fn new(shape: &str) -> Option<Self> {
         match shape {
-            'A' => RPS::ROCK,
-            'B' => RPS::PAPER,
-            'C' => RPS::SCISORS,
-            _ => panic!("Invalid opponent shape character. Only allowed A, B or C")
+            "A" => Some(RPS::ROCK),
+            "B" => Some(RPS::PAPER),
+            "C" => Some(RPS::SCISORS),
+            _ => None
         }
     }
 
@@ -63,15 +92,16 @@ impl RPS {
     MyShape or OpponentShape, I know characters are bound to be one of these matches and I don't
     have to check everywhere else in the program.
  */
+#[derive(PartialEq, Copy, Clone, Debug)]
 enum MyShape {X, Y, Z}
 
 impl MyShape {
-    fn new(shape: char) -> Self {
+    fn new(shape: &str) -> Option<Self> {
         match shape {
-            'X' => MyShape::X,
-            'Y' => MyShape::Y,
-            'Z' => MyShape::Z,
-            _ => panic!("Invalid my shape character. Only allowed X, Y or Z")
+            "X" => Some(MyShape::X),
+            "Y" => Some(MyShape::Y),
+            "Z" => Some(MyShape::Z),
+            _ => None
         }
     }
 }
@@ -92,13 +122,13 @@ enum MyOutcome {
     Z(RPS)
 }
 
+/// The second column is reinterpreted as the outcome I'm supposed to reach against opponent_shape
 impl MyOutcome {
-    fn new(shape: char, opponent_shape: RPS) -> Self {
+    fn new(shape: MyShape, opponent_shape: RPS) -> Self {
         match shape {
-            'X' => MyOutcome::X(opponent_shape),
-            'Y' => MyOutcome::Y(opponent_shape),
-            'Z' => MyOutcome::Z(opponent_shape),
-            _ => panic!("Invalid my outcome character. Only allowed X, Y or Z")
+            MyShape::X => MyOutcome::X(opponent_shape),
+            MyShape::Y => MyOutcome::Y(opponent_shape),
+            MyShape::Z => MyOutcome::Z(opponent_shape),
         }
     }
 }
@@ -113,30 +143,105 @@ impl From<MyOutcome> for RPS {
     }
 }
 
-/// Response to the first part
-pub fn total_rps_score_with_my_shape(plays: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut total_score = 0;
-    for line in plays {
-        if let Some((opponent_shape, my_shape)) = line.as_ref().trim().split_once(' ') {
-            let opponent_shape = RPS::new(opponent_shape.chars().next().expect("Can at most be empty"));
-            let my_shape = MyShape::new(my_shape.chars().next().expect("Can at most be empty"));
-            total_score += RPS::from(my_shape).round_score(opponent_shape);
+/// Every round of the strategy guide as the opponent shape and the second column of the line
+#[derive(PartialEq, Debug)]
+pub struct RockPaperScissors {
+    rounds: Vec<(RPS, MyShape)>,
+}
+
+impl RockPaperScissors {
+    /// Blank lines are skipped. Every other line must be exactly `<A|B|C> <X|Y|Z>`.
+    pub fn from_lines(plays: impl Iterator<Item=impl AsRef<str>>) -> Result<Self, ParseError> {
+        let mut rounds = vec![];
+        let plays: Vec<_> = plays.collect();
+        for line in parsing::lines(&plays).map(Token::trim) {
+            if line.is_empty() {
+                continue;
+            }
+            let (opponent_shape, my_shape) = line.key_value().map_err(error_at!(ParseError::MissingSeparator))?;
+            let opponent_shape = RPS::new(opponent_shape.as_str())
+                .ok_or_else(|| error_at!(ParseError::InvalidOpponentShape)(opponent_shape))?;
+            let my_shape = MyShape::new(my_shape.as_str())
+                .ok_or_else(|| error_at!(ParseError::InvalidMyShape)(my_shape))?;
+            rounds.push((opponent_shape, my_shape));
         }
+        Ok(Self { rounds })
+    }
+
+    fn total_score_with_my_shape(&self) -> usize {
+        self.rounds
+            .iter()
+            .map(|&(opponent_shape, my_shape)| {
+                let score = RPS::from(my_shape).round_score(opponent_shape);
+                debug!(?opponent_shape, ?my_shape, score, "played round");
+                score
+            })
+            .sum()
+    }
+
+    fn total_score_with_outcome(&self) -> usize {
+        self.rounds
+            .iter()
+            .map(|&(opponent_shape, my_shape)| {
+                let shape = RPS::from(MyOutcome::new(my_shape, opponent_shape));
+                let score = shape.round_score(opponent_shape);
+                debug!(?opponent_shape, ?my_shape, ?shape, score, "played round");
+                score
+            })
+            .sum()
     }
-    total_score
 }
 
-/// Response to the second part
-pub fn total_rps_score_with_outcome(plays: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut total_score = 0;
-    for line in plays {
-        if let Some((opponent_shape, my_shape)) = line.as_ref().trim().split_once(' ') {
-            let opponent_shape = RPS::new(opponent_shape.chars().next().expect("Can at most be empty"));
-            let my_outcome = MyOutcome::new(my_shape.chars().next().expect("Can at most be empty"), opponent_shape);
-            total_score += RPS::from(my_outcome).round_score(opponent_shape);
+impl Solution for RockPaperScissors {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines())?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(self.total_score_with_my_shape().into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(self.total_score_with_outcome().into())
+    }
+}
+
+impl fmt::Display for RockPaperScissors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (opponent_shape, my_shape) in &self.rounds {
+            let opponent_shape = match opponent_shape {
+                RPS::ROCK => "A",
+                RPS::PAPER => "B",
+                RPS::SCISORS => "C",
+            };
+            let my_shape = match my_shape {
+                MyShape::X => "X",
+                MyShape::Y => "Y",
+                MyShape::Z => "Z",
+            };
+            writeln!(f, "{} {}", opponent_shape, my_shape)?;
         }
+        Ok(())
     }
-    total_score
+}
+
+impl Generator for RockPaperScissors {
+    /// size rounds, one per line
+    fn generate(random: &mut Random, size: usize) -> String {
+        (0..size)
+            .map(|_| format!("{} {}\n", ["A", "B", "C"][random.random_range(0..3)], ["X", "Y", "Z"][random.random_range(0..3)]))
+            .collect()
+    }
+}
+
+/// Response to the first part
+pub fn total_rps_score_with_my_shape(plays: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    Ok(RockPaperScissors::from_lines(plays)?.total_score_with_my_shape())
+}
+
+/// Response to the second part
+pub fn total_rps_score_with_outcome(plays: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    Ok(RockPaperScissors::from_lines(plays)?.total_score_with_outcome())
 }
 
 #[cfg(test)]
@@ -146,7 +251,7 @@ mod tests {
     #[test]
     fn test_total_rpx_score_with_my_play_one_play() {
         assert_eq!(total_rps_score_with_my_shape("\
-A Y".lines()), 8)
+A Y".lines()), Ok(8))
     }
 
     #[test]
@@ -154,13 +259,13 @@ A Y".lines()), 8)
         assert_eq!(total_rps_score_with_my_shape("\
 A Y
 B X
-C Z".lines()), 15)
+C Z".lines()), Ok(15))
     }
 
     #[test]
     fn test_total_rpx_score_with_outcome_one_play() {
         assert_eq!(total_rps_score_with_outcome("\
-A Y".lines()), 4)
+A Y".lines()), Ok(4))
     }
 
     #[test]
@@ -168,6 +273,36 @@ A Y".lines()), 4)
         assert_eq!(total_rps_score_with_outcome("\
 A Y
 B X
-C Z".lines()), 12)
+C Z".lines()), Ok(12))
+    }
+
+    #[test]
+    fn test_rock_paper_scissors_solution() {
+        let rock_paper_scissors = RockPaperScissors::parse("\
+A Y
+B X
+C Z").unwrap();
+        assert_eq!(rock_paper_scissors.part1().unwrap(), Answer::Number(15));
+        assert_eq!(rock_paper_scissors.part2().unwrap(), Answer::Number(12));
+    }
+
+    #[test]
+    fn test_total_rps_score_invalid_lines() {
+        assert_eq!(total_rps_score_with_my_shape("A Y\nBX".lines()),
+                   Err(ParseError::MissingSeparator { line: 2, column: 1, text: "BX".to_string() }));
+        assert_eq!(total_rps_score_with_my_shape("A Y\n\nD X".lines()),
+                   Err(ParseError::InvalidOpponentShape { line: 3, column: 1, text: "D".to_string() }));
+        assert_eq!(total_rps_score_with_outcome("A Yes".lines()),
+                   Err(ParseError::InvalidMyShape { line: 1, column: 3, text: "Yes".to_string() }));
+    }
+
+    #[test]
+    fn test_generator() {
+        assert_eq!(RockPaperScissors::generate(&mut solution::random(1), 50).lines().count(), 50);
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<RockPaperScissors>();
     }
 }
\ No newline at end of file
@@ -1,60 +1,170 @@
 use std::collections::HashSet;
+use std::{error, fmt};
+use parsing::Token;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::Rng;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::debug;
 
-pub mod input;
+/**
+    Errors found while parsing rucksacks. Lines and columns start at 1. Group errors point at the
+    first rucksack of the offending group.
+*/
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    OddNumberOfItems { line: usize, column: usize, text: String },
+    InvalidItem { line: usize, column: usize, text: String },
+    IncompleteGroup { line: usize, column: usize, text: String },
+    MissingBadge { line: usize, column: usize, text: String },
+}
 
-/// Response to the first part
-pub fn rucksacks_priorities_sum(lines: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut rucksacks_priorities_sum = 0;
-    for line in lines {
-        let line = line.as_ref().trim();
-        rucksacks_priorities_sum += Rucksack::new(line.to_string()).shared_priorities_sum()
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::OddNumberOfItems { line, column, text } =>
+                write!(f, "line {}, column {}: expected an even number of items, found `{}`", line, column, text),
+            ParseError::InvalidItem { line, column, text } =>
+                write!(f, "line {}, column {}: expected an item from a to z or A to Z, found `{}`", line, column, text),
+            ParseError::IncompleteGroup { line, column, text } =>
+                write!(f, "line {}, column {}: expected a group of three rucksacks starting at `{}`", line, column, text),
+            ParseError::MissingBadge { line, column, text } =>
+                write!(f, "line {}, column {}: expected an item shared by the group starting at `{}`", line, column, text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Every rucksack in the input, in order, so that groups of three can be formed
+#[derive(PartialEq, Debug)]
+pub struct RucksackReorganization {
+    rucksacks: Vec<Rucksack>,
+}
+
+impl RucksackReorganization {
+    pub fn from_lines(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<Self, ParseError> {
+        let lines: Vec<_> = lines.collect();
+        Ok(Self {
+            rucksacks: parsing::lines(&lines)
+                .map(Token::trim)
+                .map(|line| Rucksack::new(line.as_str().to_string(), line.line(), line.column()))
+                .collect::<Result<_, _>>()?
+        })
+    }
+
+    fn priorities_sum(&self) -> usize {
+        self.rucksacks
+            .iter()
+            .map(Rucksack::shared_priorities_sum)
+            .sum()
+    }
+
+    fn group_badges_sum(&self) -> Result<usize, ParseError> {
+        self.rucksacks
+            .chunks(3)
+            .map(|group| match group {
+                [first, second, third] => get_item_intersection_in_rucksacks(first, second, third)
+                    .inspect(|badge| debug!(line = first.line, badge = %badge, "found group badge"))
+                    .map(|item| get_priority(&item))
+                    .ok_or_else(|| first.error(|line, column, text| ParseError::MissingBadge { line, column, text })),
+                _ => Err(group[0].error(|line, column, text| ParseError::IncompleteGroup { line, column, text })),
+            })
+            .sum()
+    }
+}
+
+impl Solution for RucksackReorganization {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines())?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(self.priorities_sum().into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(self.group_badges_sum()?.into())
     }
-    rucksacks_priorities_sum
+}
+
+impl fmt::Display for RucksackReorganization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rucksack in &self.rucksacks {
+            writeln!(f, "{}", rucksack.items)?;
+        }
+        Ok(())
+    }
+}
+
+impl Generator for RucksackReorganization {
+    /**
+        size groups of three rucksacks. The items of a group, but its badge, are split between its
+        three rucksacks so that the badge is the only item they share. Likewise, the two
+        compartments of a rucksack draw from disjoint halves of its items but for a single shared
+        item.
+    */
+    fn generate(random: &mut Random, size: usize) -> String {
+        let mut rucksacks = String::new();
+        for _ in 0..size {
+            let mut items: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+            items.shuffle(random);
+            let badge = items.pop().expect("52 items to pick from");
+            for rucksack_items in items.chunks(17) {
+                let shared = if random.random_bool(0.2) { badge } else { *rucksack_items.choose(random).expect("17 items") };
+                let (first_items, second_items) = rucksack_items.split_at(8);
+                let compartment_size = random.random_range(2..=12);
+                let mut first_compartment = vec![shared, badge];
+                let mut second_compartment = vec![shared];
+                for (compartment, items) in [(&mut first_compartment, first_items), (&mut second_compartment, second_items)] {
+                    compartment.truncate(compartment_size);
+                    while compartment.len() < compartment_size {
+                        compartment.push(*items.choose(random).expect("8 items at least"));
+                    }
+                    compartment.shuffle(random);
+                }
+                rucksacks.extend(first_compartment.into_iter().chain(second_compartment));
+                rucksacks.push('\n');
+            }
+        }
+        rucksacks
+    }
+}
+
+/// Response to the first part
+pub fn rucksacks_priorities_sum(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    Ok(RucksackReorganization::from_lines(lines)?.priorities_sum())
 }
 
 /// Response to the second part
-pub fn rucksacks_group_badges_sum(lines: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut rucksacks_group_badges_sum = 0;
-    let mut lines = lines.into_iter();
-    while let Some(first_rucksack) = lines.next() {
-        let first_rucksack = Rucksack::new(first_rucksack.as_ref().to_string());
-        let second_rucksack = Rucksack::new(
-            lines
-                .next()
-                .expect("The number of rucksacks should always be divisible by 3.")
-                .as_ref()
-                .to_string());
-        let third_rucksack = Rucksack::new(
-            lines
-                .next()
-                .expect("The number of rucksacks should always be divisible by 3.")
-                .as_ref()
-                .to_string());
-
-        rucksacks_group_badges_sum += get_priority(&get_item_intersection_in_rucksacks(
-            first_rucksack,
-            second_rucksack,
-            third_rucksack));
-    }
-    rucksacks_group_badges_sum
+pub fn rucksacks_group_badges_sum(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    RucksackReorganization::from_lines(lines)?.group_badges_sum()
 }
 
+/// Rucksacks remember where they were read from so that group errors can point back to them
+#[derive(PartialEq, Debug)]
 struct Rucksack {
     items: String,
+    line: usize,
+    column: usize,
 }
 
 impl Rucksack {
 
-    fn new(items: String) -> Self {
-        if items.len() % 2 != 0 {
-            panic!("There should always be an even number of items.");
-        }
-        for item in items.chars() {
-            if !item.is_alphabetic() {
-                panic!("There should always be only alphabetic items.");
+    fn new(items: String, line: usize, column: usize) -> Result<Self, ParseError> {
+        for (index, item) in items.chars().enumerate() {
+            if !item.is_ascii_alphabetic() {
+                return Err(ParseError::InvalidItem { line, column: column + index, text: item.to_string() });
             }
         }
-        Self {items}
+        // Only ASCII items at this point, so bytes and items can be used interchangeably
+        if !items.len().is_multiple_of(2) {
+            return Err(ParseError::OddNumberOfItems { line, column, text: items });
+        }
+        Ok(Self { items, line, column })
+    }
+
+    fn error(&self, variant: fn(usize, usize, String) -> ParseError) -> ParseError {
+        variant(self.line, self.column, self.items.clone())
     }
 
     fn shared_priorities_sum(&self) -> usize {
@@ -64,8 +174,9 @@ impl Rucksack {
         let mut priorities_sum = 0;
 
         for item in &first_compartment {
-            if second_compartment.contains(&item) {
-                priorities_sum += get_priority(&item);
+            if second_compartment.contains(item) {
+                debug!(line = self.line, item = %item, "found shared item");
+                priorities_sum += get_priority(item);
             }
         }
         priorities_sum
@@ -82,7 +193,7 @@ fn get_priority(item: &char) -> usize {
     (if  item >= ('a' as u32) && item <= ('z' as u32) {
         item - ('a' as u32) + 1
     } else {
-        // we don't need to check uppercase range because new() guarantees we have only alphabetic characters
+        // we don't need to check uppercase range because new() guarantees we have only ASCII alphabetic characters
         item - ('A' as u32) + 27
     }) as usize
 }
@@ -91,11 +202,11 @@ fn get_set_with_chars(items: &str) -> HashSet<char> {
     items.chars().collect()
 }
 
-fn get_item_intersection_in_rucksacks(first: Rucksack, second: Rucksack, third: Rucksack) -> char {
+fn get_item_intersection_in_rucksacks(first: &Rucksack, second: &Rucksack, third: &Rucksack) -> Option<char> {
     let intersection_set =
         &(&first.get_set() & &second.get_set())
         & &third.get_set();
-    intersection_set.into_iter().next().expect("There should be at least one intersected item.")
+    intersection_set.into_iter().next()
 }
 
 #[cfg(test)]
@@ -103,25 +214,27 @@ mod test {
     use super::*;
 
     #[test]
-    #[should_panic]
     fn test_rucksack_new_odd_number_of_items() {
-        Rucksack::new("abc".to_string());
+        assert_eq!(Rucksack::new("abc".to_string(), 4, 2).err(),
+                   Some(ParseError::OddNumberOfItems { line: 4, column: 2, text: "abc".to_string() }));
     }
 
     #[test]
-    #[should_panic]
     fn test_rucksack_new_non_alphabetic_items() {
-        Rucksack::new("ab1c".to_string());
+        assert_eq!(Rucksack::new("ab1c".to_string(), 4, 2).err(),
+                   Some(ParseError::InvalidItem { line: 4, column: 4, text: "1".to_string() }));
+        assert_eq!(Rucksack::new("abéc".to_string(), 4, 1).err(),
+                   Some(ParseError::InvalidItem { line: 4, column: 3, text: "é".to_string() }));
     }
 
     #[test]
     fn test_rucksack_shared_common_priority() {
-        assert_eq!(Rucksack::new("vJrwpWtwJgWrhcsFMMfFFhFp".to_string()).shared_priorities_sum(), 16);
-        assert_eq!(Rucksack::new("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL".to_string()).shared_priorities_sum(), 38);
-        assert_eq!(Rucksack::new("PmmdzqPrVvPwwTWBwg".to_string()).shared_priorities_sum(), 42);
-        assert_eq!(Rucksack::new("wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn".to_string()).shared_priorities_sum(), 22);
-        assert_eq!(Rucksack::new("ttgJtRGJQctTZtZT".to_string()).shared_priorities_sum(), 20);
-        assert_eq!(Rucksack::new("CrZsJsPPZsGzwwsLwLmpwMDw".to_string()).shared_priorities_sum(), 19);
+        assert_eq!(Rucksack::new("vJrwpWtwJgWrhcsFMMfFFhFp".to_string(), 1, 1).unwrap().shared_priorities_sum(), 16);
+        assert_eq!(Rucksack::new("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL".to_string(), 1, 1).unwrap().shared_priorities_sum(), 38);
+        assert_eq!(Rucksack::new("PmmdzqPrVvPwwTWBwg".to_string(), 1, 1).unwrap().shared_priorities_sum(), 42);
+        assert_eq!(Rucksack::new("wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn".to_string(), 1, 1).unwrap().shared_priorities_sum(), 22);
+        assert_eq!(Rucksack::new("ttgJtRGJQctTZtZT".to_string(), 1, 1).unwrap().shared_priorities_sum(), 20);
+        assert_eq!(Rucksack::new("CrZsJsPPZsGzwwsLwLmpwMDw".to_string(), 1, 1).unwrap().shared_priorities_sum(), 19);
     }
 
     #[test]
@@ -132,7 +245,7 @@ jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
 wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
-CrZsJsPPZsGzwwsLwLmpwMDw".lines()), 157);
+CrZsJsPPZsGzwwsLwLmpwMDw".lines()), Ok(157));
     }
 
     #[test]
@@ -143,6 +256,53 @@ jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
 wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
-CrZsJsPPZsGzwwsLwLmpwMDw".lines()), 70);
+CrZsJsPPZsGzwwsLwLmpwMDw".lines()), Ok(70));
+    }
+
+    #[test]
+    fn test_rucksack_reorganization_solution() {
+        let rucksack_reorganization = RucksackReorganization::parse("\
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw").unwrap();
+        assert_eq!(rucksack_reorganization.part1().unwrap(), Answer::Number(157));
+        assert_eq!(rucksack_reorganization.part2().unwrap(), Answer::Number(70));
+    }
+
+    #[test]
+    fn test_rucksacks_group_badges_sum_invalid_groups() {
+        assert_eq!(rucksacks_group_badges_sum("\
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn".lines()),
+                   Err(ParseError::IncompleteGroup { line: 4, column: 1, text: "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn".to_string() }));
+        assert_eq!(rucksacks_group_badges_sum("ab\ncd\nef".lines()),
+                   Err(ParseError::MissingBadge { line: 1, column: 1, text: "ab".to_string() }));
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = RucksackReorganization::generate(&mut solution::random(seed), 10);
+            assert_eq!(input.lines().count(), 30);
+            let rucksacks = RucksackReorganization::parse(&input).unwrap();
+            for rucksack in &rucksacks.rucksacks {
+                let mid_point = rucksack.items.len() / 2;
+                let shared = &get_set_with_chars(&rucksack.items[..mid_point]) & &get_set_with_chars(&rucksack.items[mid_point..]);
+                assert_eq!(shared.len(), 1, "{}", rucksack.items);
+            }
+            for group in rucksacks.rucksacks.chunks(3) {
+                assert_eq!((&(&group[0].get_set() & &group[1].get_set()) & &group[2].get_set()).len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<RucksackReorganization>();
     }
 }
\ No newline at end of file
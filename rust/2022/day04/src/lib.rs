@@ -1,52 +1,123 @@
-pub mod input;
-
-pub fn fully_overlapped_pairs(lines: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut fully_contained_pairs = 0;
-    for line in lines {
-        let line = line.as_ref().trim();
-
-        let (first_pair_start,
-            first_pair_end,
-            second_pair_start,
-            second_pair_end) = parse_ranges(line).expect("Malformed line doesn't contain comma (,) separator");
-        if range_fully_overlaps(first_pair_start, first_pair_end, second_pair_start, second_pair_end) {
-            fully_contained_pairs += 1;
+use std::{error, fmt};
+use parsing::{error_at, Token};
+use rand::Rng;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::debug;
+
+/// Errors found while parsing section assignments. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    MissingComma { line: usize, column: usize, text: String },
+    MissingDash { line: usize, column: usize, text: String },
+    InvalidSection { line: usize, column: usize, text: String },
+    ReversedRange { line: usize, column: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingComma { line, column, text } =>
+                write!(f, "line {}, column {}: expected `A-B,C-D`, found `{}`", line, column, text),
+            ParseError::MissingDash { line, column, text } =>
+                write!(f, "line {}, column {}: expected a range `A-B`, found `{}`", line, column, text),
+            ParseError::InvalidSection { line, column, text } =>
+                write!(f, "line {}, column {}: expected a section number, found `{}`", line, column, text),
+            ParseError::ReversedRange { line, column, text } =>
+                write!(f, "line {}, column {}: expected range start to be at most its end, found `{}`", line, column, text),
         }
     }
-    fully_contained_pairs
 }
 
-pub fn partially_overlapped_pairs(lines: impl Iterator<Item=impl AsRef<str>>) -> usize {
-    let mut partially_overlapped_pairs = 0;
-    for line in lines {
-        let line = line.as_ref().trim();
-
-        let (first_pair_start,
-            first_pair_end,
-            second_pair_start,
-            second_pair_end) = parse_ranges(line).expect("Malformed line doesn't contain comma (,) separator");
-        if range_partially_overlaps(first_pair_start, first_pair_end, second_pair_start, second_pair_end) {
-            partially_overlapped_pairs += 1;
+impl error::Error for ParseError {}
+
+/// Section ranges of each pair of elves as (first start, first end, second start, second end)
+#[derive(PartialEq, Debug)]
+pub struct CampCleanup {
+    pairs: Vec<(usize, usize, usize, usize)>,
+}
+
+impl CampCleanup {
+    pub fn from_lines(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<Self, ParseError> {
+        Ok(Self {
+            pairs: lines
+                .enumerate()
+                .map(|(index, line)| parse_ranges(index + 1, line.as_ref()))
+                .collect::<Result<_, _>>()?
+        })
+    }
+
+    fn count_pairs(&self, overlaps: fn(usize, usize, usize, usize) -> bool) -> usize {
+        self.pairs
+            .iter()
+            .filter(|&&(first_pair_start, first_pair_end, second_pair_start, second_pair_end)| {
+                let overlapping = overlaps(first_pair_start, first_pair_end, second_pair_start, second_pair_end);
+                debug!(first_pair_start, first_pair_end, second_pair_start, second_pair_end, overlapping, "compared pair");
+                overlapping
+            })
+            .count()
+    }
+}
+
+impl Solution for CampCleanup {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines())?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(self.count_pairs(range_fully_overlaps).into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(self.count_pairs(range_partially_overlaps).into())
+    }
+}
+
+impl fmt::Display for CampCleanup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (first_pair_start, first_pair_end, second_pair_start, second_pair_end) in &self.pairs {
+            writeln!(f, "{}-{},{}-{}", first_pair_start, first_pair_end, second_pair_start, second_pair_end)?;
         }
+        Ok(())
+    }
+}
+
+impl Generator for CampCleanup {
+    /// size pairs of section ranges, within sections 1 to 99
+    fn generate(random: &mut Random, size: usize) -> String {
+        let mut range = || {
+            let start = random.random_range(1..=99);
+            format!("{}-{}", start, random.random_range(start..=99))
+        };
+        (0..size).map(|_| format!("{},{}\n", range(), range())).collect()
     }
-    partially_overlapped_pairs
 }
 
-fn parse_ranges(line: &str) -> Option<(usize, usize, usize, usize)> {
-    if let Some((first_pair, second_pair)) = line.split_once(',') {
-        let (first_pair_start, first_pair_end) = first_pair.split_once('-')
-            .expect("First range should contain a dash (-) separator.");
-        let first_pair_start: usize = first_pair_start.parse().expect("First pair start should be numeric.");
-        let first_pair_end: usize = first_pair_end.parse().expect("First pair end should be numeric.");
+pub fn fully_overlapped_pairs(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    Ok(CampCleanup::from_lines(lines)?.count_pairs(range_fully_overlaps))
+}
+
+pub fn partially_overlapped_pairs(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<usize, ParseError> {
+    Ok(CampCleanup::from_lines(lines)?.count_pairs(range_partially_overlaps))
+}
 
-        let (second_pair_start, second_pair_end) = second_pair.split_once('-')
-            .expect("Range should contain a dash (-) separator.");
-        let second_pair_start: usize = second_pair_start.parse().expect("Second pair start should be numeric.");
-        let second_pair_end: usize = second_pair_end.parse().expect("Second pair end should be numeric.");
+/// Parses `A-B,C-D` where line_number is used for error reporting only
+fn parse_ranges(line_number: usize, raw_line: &str) -> Result<(usize, usize, usize, usize), ParseError> {
+    let line = Token::new(line_number, raw_line).trim();
+    let (first_pair, second_pair) = line.split_once(",").map_err(error_at!(ParseError::MissingComma))?;
+    let (first_pair_start, first_pair_end) = parse_range(first_pair)?;
+    let (second_pair_start, second_pair_end) = parse_range(second_pair)?;
+    Ok((first_pair_start, first_pair_end, second_pair_start, second_pair_end))
+}
 
-        return Some((first_pair_start, first_pair_end, second_pair_start, second_pair_end));
+/// Parses a single `A-B` range
+fn parse_range(range: Token) -> Result<(usize, usize), ParseError> {
+    let (start, end) = range.split_once("-").map_err(error_at!(ParseError::MissingDash))?;
+    let parse_section = |section: Token| section.integer::<usize>().map_err(error_at!(ParseError::InvalidSection));
+    let (start, end) = (parse_section(start)?, parse_section(end)?);
+    if start > end {
+        return Err(error_at!(ParseError::ReversedRange)(range));
     }
-    None
+    Ok((start, end))
 }
 
 /// Response to the first part
@@ -83,7 +154,7 @@ mod test {
 5-7,7-9
 2-8,3-7
 6-6,4-6
-2-6,4-8".lines()), 2);
+2-6,4-8".lines()), Ok(2));
     }
 
     #[test]
@@ -94,6 +165,41 @@ mod test {
 5-7,7-9
 2-8,3-7
 6-6,4-6
-2-6,4-8".lines()), 4);
+2-6,4-8".lines()), Ok(4));
+    }
+
+    #[test]
+    fn test_camp_cleanup_solution() {
+        let camp_cleanup = CampCleanup::parse("\
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8").unwrap();
+        assert_eq!(camp_cleanup.part1().unwrap(), Answer::Number(2));
+        assert_eq!(camp_cleanup.part2().unwrap(), Answer::Number(4));
+    }
+
+    #[test]
+    fn test_parse_ranges_errors() {
+        assert_eq!(parse_ranges(3, "2-4 6-8"),
+                   Err(ParseError::MissingComma { line: 3, column: 1, text: "2-4 6-8".to_string() }));
+        assert_eq!(parse_ranges(3, "2-4,68"),
+                   Err(ParseError::MissingDash { line: 3, column: 5, text: "68".to_string() }));
+        assert_eq!(parse_ranges(3, " 2-x,6-8"),
+                   Err(ParseError::InvalidSection { line: 3, column: 4, text: "x".to_string() }));
+        assert_eq!(parse_ranges(3, "4-2,6-8"),
+                   Err(ParseError::ReversedRange { line: 3, column: 1, text: "4-2".to_string() }));
+    }
+
+    #[test]
+    fn test_generator() {
+        assert_eq!(CampCleanup::generate(&mut solution::random(1), 50).lines().count(), 50);
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<CampCleanup>();
     }
 }
\ No newline at end of file
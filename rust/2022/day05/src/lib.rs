@@ -1,11 +1,45 @@
+use std::cmp;
 use std::collections::HashMap;
+use std::{error, fmt};
+use parsing::{error_at, Token};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use solution::{count_argument, unknown_command, Answer, Error, Generator, Random, Repl, Solution};
+use tracing::{debug, trace};
+
+/// Errors found while parsing the drawing and the move instructions. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    MissingStackNames { line: usize, column: usize, text: String },
+    DuplicateStackName { line: usize, column: usize, text: String },
+    InvalidCrate { line: usize, column: usize, text: String },
+    InvalidMoveInstruction { line: usize, column: usize, text: String },
+    UnknownStack { line: usize, column: usize, text: String },
+}
 
-pub mod input;
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingStackNames { line, column, text } =>
+                write!(f, "line {}, column {}: expected a line with stack names, found `{}`", line, column, text),
+            ParseError::DuplicateStackName { line, column, text } =>
+                write!(f, "line {}, column {}: expected unique stack names, found `{}` twice", line, column, text),
+            ParseError::InvalidCrate { line, column, text } =>
+                write!(f, "line {}, column {}: expected an ASCII crate drawing, found `{}`", line, column, text),
+            ParseError::InvalidMoveInstruction { line, column, text } =>
+                write!(f, "line {}, column {}: expected `move N from A to B`, found `{}`", line, column, text),
+            ParseError::UnknownStack { line, column, text } =>
+                write!(f, "line {}, column {}: expected a stack name from the drawing, found `{}`", line, column, text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
 
 type CrateStacksMap = HashMap<String, Vec<String>>;
 
 /// We store ordered_stack_names in order to create crate_stacks in the order it was inserted
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 pub struct CrateStacks {
     ordered_stack_names: Vec<String>,
     crate_stacks: CrateStacksMap,
@@ -26,6 +60,8 @@ impl CrateStacks {
     }
 
     fn move_many_from_top(&mut self, n: usize, from_stack: &str, to_stack: &str) {
+        // Don't spin over more moves than there are crates, n may be as large as usize::MAX
+        let n = self.crate_stacks.get(from_stack).map_or(0, Vec::len).min(n);
         for _ in 0..n {
             self.move_from_to_stack(from_stack, to_stack);
         }
@@ -63,6 +99,20 @@ impl CrateStacks {
         }
     }
 
+    /// Applies each instruction in order using either move_many_from_top or move_many_from_bottom
+    fn apply_move_instructions(&mut self,
+                               move_instructions: &[MoveInstruction],
+                               move_many: fn(&mut Self, usize, &str, &str)) {
+        move_instructions
+            .iter()
+            .for_each(|move_instruction| {
+                let MoveInstruction { n, from_stack, to_stack } = move_instruction;
+                debug!(n, from_stack = from_stack.as_str(), to_stack = to_stack.as_str(), "move");
+                move_many(self, *n, from_stack, to_stack);
+                trace!(top_crates = %self.get_top_crates(), "moved");
+            });
+    }
+
     /// Returns top crates from stacks as string. If a stack doesn't have any crates, return a blank space.
     fn get_top_crates(&self) -> String {
         self.ordered_stack_names
@@ -70,7 +120,7 @@ impl CrateStacks {
             .map(|crate_name|
                 self.crate_stacks
                     .get(crate_name)
-                    .expect(&format!("Expect there to be a stack for crate: {}", crate_name))
+                    .unwrap_or_else(|| panic!("Expect there to be a stack for crate: {}", crate_name))
                     .last()
                     .map(String::as_str))
             .map(|a| if a.is_none() { Some(" ") } else { a }) // problem statement didn't mention this case
@@ -80,6 +130,191 @@ impl CrateStacks {
     }
 }
 
+/**
+    Renders the drawing of the stacks: crates from the highest level down, one column of 4
+    characters per stack, followed by the stack names.
+*/
+impl fmt::Display for CrateStacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stacks: Vec<&Vec<String>> = self.ordered_stack_names
+            .iter()
+            .map(|name| &self.crate_stacks[name])
+            .collect();
+        let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = stacks
+                .iter()
+                .map(|stack| stack.get(level).map_or("   ".to_string(), |crate_name| format!("[{}]", crate_name)))
+                .collect::<Vec<String>>()
+                .join(" ");
+            writeln!(f, "{}", row.trim_end())?;
+        }
+        writeln!(f, " {}", self.ordered_stack_names.join("   "))
+    }
+}
+
+/// Move <n> crates from <from_stack> to <to_stack>
+#[derive(PartialEq, Debug)]
+pub struct MoveInstruction {
+    n: usize,
+    from_stack: String,
+    to_stack: String
+}
+
+impl fmt::Display for MoveInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.n, self.from_stack, self.to_stack)
+    }
+}
+
+/// Starting crate stacks together with the move instructions that rearrange them
+#[derive(PartialEq, Debug)]
+pub struct SupplyStacks {
+    crate_stacks: CrateStacks,
+    move_instructions: Vec<MoveInstruction>,
+}
+
+impl SupplyStacks {
+    pub fn from_lines(lines: impl Iterator<Item=String>) -> Result<Self, ParseError> {
+        let (crate_stacks, move_instructions) = crate_stacks_lines_parser::parse_from_lines(lines)?;
+        Ok(Self { crate_stacks, move_instructions })
+    }
+
+    /// Rearranges a copy of the starting stacks so that both parts can start from the same state
+    fn get_top_crates_after_moves(&self, move_many: fn(&mut CrateStacks, usize, &str, &str)) -> String {
+        let mut crate_stacks = self.crate_stacks.clone();
+        crate_stacks.apply_move_instructions(&self.move_instructions, move_many);
+        crate_stacks.get_top_crates()
+    }
+}
+
+impl Solution for SupplyStacks {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines().map(String::from))?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(self.get_top_crates_after_moves(CrateStacks::move_many_from_top).into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(self.get_top_crates_after_moves(CrateStacks::move_many_from_bottom).into())
+    }
+}
+
+impl fmt::Display for SupplyStacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.crate_stacks)?;
+        for move_instruction in &self.move_instructions {
+            writeln!(f, "{}", move_instruction)?;
+        }
+        Ok(())
+    }
+}
+
+/// Applies the moves one at a time with either crane, see aoc repl
+pub struct SupplyStacksRepl {
+    supply_stacks: SupplyStacks,
+    crate_stacks: CrateStacks,
+    applied_instructions: usize,
+    crane: usize,
+}
+
+impl SupplyStacksRepl {
+    /// Moves crates one at a time with the CrateMover 9000, or several at once with the 9001
+    fn move_many(&self) -> fn(&mut CrateStacks, usize, &str, &str) {
+        match self.crane {
+            9000 => CrateStacks::move_many_from_top,
+            _ => CrateStacks::move_many_from_bottom,
+        }
+    }
+
+    /// Applies up to count of the remaining moves, returning them followed by the resulting drawing
+    fn step(&mut self, count: usize) -> String {
+        let end = cmp::min(self.applied_instructions.saturating_add(count), self.supply_stacks.move_instructions.len());
+        let instructions = &self.supply_stacks.move_instructions[self.applied_instructions..end];
+        self.crate_stacks.apply_move_instructions(instructions, self.move_many());
+        self.applied_instructions = end;
+        let mut output: Vec<String> = instructions.iter().map(MoveInstruction::to_string).collect();
+        output.push(self.crate_stacks.to_string());
+        output.join("\n")
+    }
+
+    fn reset(&mut self, arguments: &[&str]) -> Result<String, Error> {
+        self.crane = match arguments {
+            [] => self.crane,
+            ["9000"] => 9000,
+            ["9001"] => 9001,
+            _ => return Err("expected `reset [9000|9001]`".into()),
+        };
+        self.crate_stacks = self.supply_stacks.crate_stacks.clone();
+        self.applied_instructions = 0;
+        Ok(self.crate_stacks.to_string())
+    }
+}
+
+impl Repl for SupplyStacksRepl {
+    fn start(input: &str) -> Result<Self, Error> {
+        let supply_stacks = SupplyStacks::parse(input)?;
+        Ok(Self { crate_stacks: supply_stacks.crate_stacks.clone(), supply_stacks, applied_instructions: 0, crane: 9000 })
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("step [n]", "applies the next n moves, 1 by default, and draws the stacks"),
+            ("run", "applies every remaining move and draws the stacks"),
+            ("show", "draws the stacks"),
+            ("top", "crates on top of each stack"),
+            ("reset [9000|9001]", "restarts from the drawing, with the CrateMover 9000 of the first part or the 9001 of the second"),
+        ]
+    }
+
+    fn prompt(&self) -> String {
+        format!("CrateMover {}, move {}/{}", self.crane, self.applied_instructions, self.supply_stacks.move_instructions.len())
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[&str]) -> Result<String, Error> {
+        match command {
+            "step" => Ok(self.step(count_argument(arguments)?)),
+            "run" => Ok(self.step(usize::MAX)),
+            "show" => Ok(self.crate_stacks.to_string()),
+            "top" => Ok(self.crate_stacks.get_top_crates()),
+            "reset" => self.reset(arguments),
+            _ => Err(unknown_command(command)),
+        }
+    }
+}
+
+impl Generator for SupplyStacks {
+    /**
+        1 to 9 stacks holding size crates in total, followed by size moves when there are at least
+        two stacks. Moves are simulated so that they never take more crates than a stack holds.
+    */
+    fn generate(random: &mut Random, size: usize) -> String {
+        let stack_count = random.random_range(1..=9);
+        let mut stacks: Vec<Vec<String>> = vec![vec![]; stack_count];
+        for _ in 0..size {
+            stacks[random.random_range(0..stack_count)].push(random.random_range('A'..='Z').to_string());
+        }
+        let ordered_stack_names: Vec<String> = (1..=stack_count).map(|name| name.to_string()).collect();
+        let crate_stacks = CrateStacks::new(ordered_stack_names.clone(), ordered_stack_names.iter().cloned().zip(stacks.clone()).collect());
+
+        let mut move_instructions = vec![];
+        for _ in 0..if stack_count > 1 { size } else { 0 } {
+            let from_stacks: Vec<usize> = (0..stack_count).filter(|&stack| !stacks[stack].is_empty()).collect();
+            let Some(&from_stack) = from_stacks.choose(random) else { break };
+            let to_stack = (from_stack + random.random_range(1..stack_count)) % stack_count;
+            let n = random.random_range(1..=stacks[from_stack].len());
+            let remaining = stacks[from_stack].len() - n;
+            let moved: Vec<String> = stacks[from_stack].drain(remaining..).collect();
+            stacks[to_stack].extend(moved);
+            move_instructions.push(MoveInstruction {
+                n, from_stack: ordered_stack_names[from_stack].clone(), to_stack: ordered_stack_names[to_stack].clone() });
+        }
+        SupplyStacks { crate_stacks, move_instructions }.to_string()
+    }
+}
+
 /**
     This is a factory module that initializes CrateStacks from Iterator<Item=String>. We could have
     other factory modules that initialize CrateStacks from different sources.
@@ -87,26 +322,16 @@ impl CrateStacks {
 pub mod crate_stacks_lines_parser {
     use super::*;
 
-    struct MoveInstruction {
-        n: usize,
-        from_stack: String,
-        to_stack: String
-    }
-
     /**
         Read the lines iterator until we reach the end of crate stacks (blank line) storing these
         lines in a Vec for creating the CrateStacks struct. Then pass the remaining iterator to
         get_iterator_with_move_instructions that will parse the instructions that will mutate
         CrateStacks.
      */
-    pub fn parse_from_top_all_instructions_from_lines(mut lines: impl Iterator<Item=String>) -> CrateStacks {
-        let mut crate_stacks = parse_crate_stacks(&mut lines);
-        get_iterator_with_move_instructions(&mut lines)
-            .for_each(|move_instruction|
-                crate_stacks.move_many_from_top(move_instruction.n,
-                                                &move_instruction.from_stack,
-                                                &move_instruction.to_stack));
-        crate_stacks
+    pub fn parse_from_top_all_instructions_from_lines(lines: impl Iterator<Item=String>) -> Result<CrateStacks, ParseError> {
+        let (mut crate_stacks, move_instructions) = parse_from_lines(lines)?;
+        crate_stacks.apply_move_instructions(&move_instructions, CrateStacks::move_many_from_top);
+        Ok(crate_stacks)
     }
 
     /**
@@ -115,68 +340,87 @@ pub mod crate_stacks_lines_parser {
         get_iterator_with_move_instructions that will parse the instructions that will mutate
         CrateStacks.
      */
-    pub fn parse_from_bottom_all_instructions_from_lines(mut lines: impl Iterator<Item=String>) -> CrateStacks {
-        let mut crate_stacks = parse_crate_stacks(&mut lines);
-        get_iterator_with_move_instructions(&mut lines)
-            .for_each(|move_instruction|
-                crate_stacks.move_many_from_bottom(move_instruction.n,
-                                                   &move_instruction.from_stack,
-                                                   &move_instruction.to_stack));
-        crate_stacks
+    pub fn parse_from_bottom_all_instructions_from_lines(lines: impl Iterator<Item=String>) -> Result<CrateStacks, ParseError> {
+        let (mut crate_stacks, move_instructions) = parse_from_lines(lines)?;
+        crate_stacks.apply_move_instructions(&move_instructions, CrateStacks::move_many_from_bottom);
+        Ok(crate_stacks)
     }
 
     /**
-        Read the lines iterator until we reach the end of crate stacks (blank line) storing these
-        lines in a Vec for creating the CrateStacks struct.
+        Parses the crate stacks drawing followed by the move instructions, without applying them.
+        The drawing is the first section of lines, up to the first blank line.
     */
-    fn parse_crate_stacks(lines: &mut impl Iterator<Item=String>) -> CrateStacks {
-        let mut crate_stacks_lines = vec![];
-        for line in lines {
-            if line.trim().is_empty() {
-                // Stop the iterator to allow parse_move_instructions_from_lines() to continue with it
-                break;
-            }
-            crate_stacks_lines.push(line.to_string());
-        }
-        let mut crate_stacks = new_crate_stacks(
-            crate_stacks_lines.pop().expect("Expect there to be a line with crate stacks names"));
-        populate_crate_stacks(&mut crate_stacks, crate_stacks_lines);
-        crate_stacks
+    pub fn parse_from_lines(lines: impl Iterator<Item=String>) -> Result<(CrateStacks, Vec<MoveInstruction>), ParseError> {
+        let lines: Vec<String> = lines.collect();
+        let mut sections = parsing::sections(parsing::lines(&lines)).into_iter();
+        let crate_stacks = parse_crate_stacks(sections.next().unwrap_or_default())?;
+        let move_instructions = get_iterator_with_move_instructions(&crate_stacks, sections.flatten())
+            .collect::<Result<_, _>>()?;
+        Ok((crate_stacks, move_instructions))
+    }
+
+    /// Creates the CrateStacks from the lines of the drawing, the last of which names the stacks
+    fn parse_crate_stacks(mut crate_stacks_lines: Vec<Token>) -> Result<CrateStacks, ParseError> {
+        let stack_names_line = crate_stacks_lines.pop()
+            .ok_or(ParseError::MissingStackNames { line: 1, column: 1, text: String::new() })?;
+        let mut crate_stacks = new_crate_stacks(stack_names_line)?;
+        populate_crate_stacks(&mut crate_stacks, crate_stacks_lines)?;
+        Ok(crate_stacks)
     }
 
-    /// Parse move instructions with the format: move <n> from <from_stack> to <to_stack>
-    fn get_iterator_with_move_instructions<'a>(lines: &'a mut impl Iterator<Item=String>) -> impl Iterator<Item=MoveInstruction> + 'a {
+    /**
+        Parse move instructions with the format: move <n> from <from_stack> to <to_stack>. Stack
+        names are checked against crate_stacks so that moves never refer to missing stacks.
+    */
+    fn get_iterator_with_move_instructions<'a>(crate_stacks: &'a CrateStacks,
+                                               lines: impl Iterator<Item=Token<'a>> + 'a)
+                                               -> impl Iterator<Item=Result<MoveInstruction, ParseError>> + 'a {
         lines.map(|line| {
-            let mut parts = line.trim().split(' ');
-            MoveInstruction {
-                n: parts.nth(1).expect("Expect to have number of crates").parse().expect("Expect to be usize"), // jump 2
-                from_stack: parts.nth(1).expect("Expect to have from stack name").to_string(), // jump 2
-                to_stack: parts.nth(1).expect("Expect to have stack name").to_string()
+            let [n, from_stack, to_stack] = line.trim().pattern("move {} from {} to {}")
+                .map_err(error_at!(ParseError::InvalidMoveInstruction))?;
+            let n = n.integer().map_err(error_at!(ParseError::InvalidMoveInstruction))?;
+            for stack_name in [from_stack, to_stack] {
+                if !crate_stacks.crate_stacks.contains_key(stack_name.as_str()) {
+                    return Err(error_at!(ParseError::UnknownStack)(stack_name));
+                }
             }
+            Ok(MoveInstruction { n, from_stack: from_stack.as_str().to_string(), to_stack: to_stack.as_str().to_string() })
         })
     }
 
     /// Creates CrateStacks but initializing all stacks with an empty Vec<String>
-    fn new_crate_stacks<'a>(line: String) -> CrateStacks {
-        let (ordered_stack_names, crate_stacks): (Vec<String>, CrateStacksMap) = line
-            .trim()
-            .split("   ")
-            .map(|name| (name.to_string(), (name.to_string(), vec![])))
-            .unzip();
+    fn new_crate_stacks(line: Token) -> Result<CrateStacks, ParseError> {
+        let mut ordered_stack_names: Vec<String> = vec![];
+        for name in line.trim().split("   ") {
+            if ordered_stack_names.iter().any(|stack_name| stack_name == name.as_str()) {
+                return Err(error_at!(ParseError::DuplicateStackName)(name));
+            }
+            ordered_stack_names.push(name.as_str().to_string());
+        }
+        let crate_stacks = ordered_stack_names
+            .iter()
+            .map(|name| (name.to_string(), vec![]))
+            .collect();
 
-        CrateStacks::new(ordered_stack_names, crate_stacks)
+        Ok(CrateStacks::new(ordered_stack_names, crate_stacks))
     }
 
-    /// Populate CrateStacks by callins add_to_stack on each index where a crate is found
-    fn populate_crate_stacks<'a>(crate_stacks: &mut CrateStacks, lines: Vec<String>) {
+    /**
+        Populate CrateStacks by callins add_to_stack on each index where a crate is found. Each
+        stack is drawn in a column of 4 characters, `[X] `, and crate names must be ASCII.
+    */
+    fn populate_crate_stacks(crate_stacks: &mut CrateStacks, lines: Vec<Token>) -> Result<(), ParseError> {
         for line in lines.into_iter().rev() {
-            (1..line.len())
-                .step_by(4)// i += 4
-                .map(|i| &line[i..i + 1])// return crate name as a slice
+            if let Some(character) = line.columns(1).find(|character| !character.as_str().is_ascii()) {
+                return Err(error_at!(ParseError::InvalidCrate)(character));
+            }
+            line.columns(4)
+                .map(|column| column.as_str().get(1..2).unwrap_or_default())// return crate name as a slice
                 .enumerate()
                 .filter(|(_, crate_name)| !crate_name.trim().is_empty())
                 .for_each(|(i, crate_name)| crate_stacks.add_to_stack(i, crate_name.to_string()))
         }
+        Ok(())
     }
 
     #[cfg(test)]
@@ -193,7 +437,7 @@ pub mod crate_stacks_lines_parser {
  1   2   3   4
 
 move 10 from 1 to 2
-move 2 from 4 to 1".lines().map(String::from)),
+move 2 from 4 to 1".lines().map(String::from)).unwrap(),
                        CrateStacks::new(
                            vec!["1", "2", "3", "4"].into_iter().map(String::from).collect(),
                            vec![
@@ -215,7 +459,7 @@ move 2 from 4 to 1".lines().map(String::from)),
  1   2   3   4
 
 move 10 from 1 to 2
-move 2 from 4 to 1".lines().map(String::from)),
+move 2 from 4 to 1".lines().map(String::from)).unwrap(),
                    CrateStacks::new(
                        vec!["1", "2", "3", "4"].into_iter().map(String::from).collect(),
                        vec![
@@ -228,13 +472,13 @@ move 2 from 4 to 1".lines().map(String::from)),
 }
 
 /// Response to the first part
-pub fn get_top_crates_after_moves_from_top(lines: impl Iterator<Item=String>) -> String {
-    crate_stacks_lines_parser::parse_from_top_all_instructions_from_lines(lines).get_top_crates()
+pub fn get_top_crates_after_moves_from_top(lines: impl Iterator<Item=String>) -> Result<String, ParseError> {
+    Ok(crate_stacks_lines_parser::parse_from_top_all_instructions_from_lines(lines)?.get_top_crates())
 }
 
 /// Response to the second part
-pub fn get_top_crates_after_moves_from_bottom(lines: impl Iterator<Item=String>) -> String {
-    crate_stacks_lines_parser::parse_from_bottom_all_instructions_from_lines(lines).get_top_crates()
+pub fn get_top_crates_after_moves_from_bottom(lines: impl Iterator<Item=String>) -> Result<String, ParseError> {
+    Ok(crate_stacks_lines_parser::parse_from_bottom_all_instructions_from_lines(lines)?.get_top_crates())
 }
 
 #[cfg(test)]
@@ -274,4 +518,85 @@ mod test {
 
         assert_eq!(crate_stacks.get_top_crates(), "cq d");
     }
+
+    #[test]
+    fn test_supply_stacks_solution() {
+        let supply_stacks = SupplyStacks::parse("    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2").unwrap();
+        assert_eq!(supply_stacks.part1().unwrap(), Answer::from("CMZ"));
+        assert_eq!(supply_stacks.part2().unwrap(), Answer::from("MCD"));
+
+        // Counts beyond the stack move the whole stack, without looping over every one of them
+        let supply_stacks = SupplyStacks::parse("[A]\n[B]\n 1   2\n\nmove 18446744073709551615 from 1 to 2").unwrap();
+        assert_eq!(supply_stacks.part1().unwrap(), Answer::from(" B"));
+        assert_eq!(supply_stacks.part2().unwrap(), Answer::from(" A"));
+    }
+
+    #[test]
+    fn test_stacks_drawing_snapshots() {
+        let example = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n";
+        let lines = || example.lines().map(String::from);
+        snapshot::assert_snapshot!("stacks_example_moved_from_top",
+                                   crate_stacks_lines_parser::parse_from_top_all_instructions_from_lines(lines()).unwrap().to_string());
+        snapshot::assert_snapshot!("stacks_example_moved_from_bottom",
+                                   crate_stacks_lines_parser::parse_from_bottom_all_instructions_from_lines(lines()).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut repl = SupplyStacksRepl::start("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n").unwrap();
+        assert_eq!(repl.prompt(), "CrateMover 9000, move 0/4");
+        assert!(repl.execute("step", &[]).unwrap().starts_with("move 1 from 2 to 1\n[D]"));
+        assert_eq!(repl.execute("top", &[]).unwrap(), "DCP");
+        repl.execute("run", &[]).unwrap();
+        assert_eq!((repl.prompt(), repl.execute("top", &[]).unwrap()), ("CrateMover 9000, move 4/4".to_string(), "CMZ".to_string()));
+        assert_eq!(repl.execute("step", &["2"]).unwrap(), repl.execute("show", &[]).unwrap());
+        repl.execute("reset", &["9001"]).unwrap();
+        repl.execute("step", &["4"]).unwrap();
+        assert_eq!((repl.prompt(), repl.execute("top", &[]).unwrap()), ("CrateMover 9001, move 4/4".to_string(), "MCD".to_string()));
+        assert!(repl.execute("reset", &["9002"]).is_err());
+    }
+
+    #[test]
+    fn test_get_top_crates_after_moves_errors() {
+        let get_top_crates = |input: &str| get_top_crates_after_moves_from_top(input.lines().map(String::from));
+
+        assert_eq!(get_top_crates("\nmove 1 from 1 to 2"),
+                   Err(ParseError::MissingStackNames { line: 1, column: 1, text: "".to_string() }));
+        assert_eq!(get_top_crates("[a]\n 1   1\n"),
+                   Err(ParseError::DuplicateStackName { line: 2, column: 6, text: "1".to_string() }));
+        assert_eq!(get_top_crates("[é]\n 1\n"),
+                   Err(ParseError::InvalidCrate { line: 1, column: 2, text: "é".to_string() }));
+        assert_eq!(get_top_crates("[a]\n 1   2\n\nmove 1 from 1 2"),
+                   Err(ParseError::InvalidMoveInstruction { line: 4, column: 1, text: "move 1 from 1 2".to_string() }));
+        assert_eq!(get_top_crates("[a]\n 1   2\n\nmove x from 1 to 2"),
+                   Err(ParseError::InvalidMoveInstruction { line: 4, column: 6, text: "x".to_string() }));
+        assert_eq!(get_top_crates("[a]\n 1   2\n\nmove 1 from 1 to 3"),
+                   Err(ParseError::UnknownStack { line: 4, column: 18, text: "3".to_string() }));
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = SupplyStacks::generate(&mut solution::random(seed), 30);
+            let solution = SupplyStacks::parse(&input).unwrap();
+            let crate_count = |crate_stacks: &CrateStacks| crate_stacks.crate_stacks.values().map(Vec::len).sum::<usize>();
+            assert_eq!(crate_count(&solution.crate_stacks), 30);
+            let mut crate_stacks = solution.crate_stacks.clone();
+            crate_stacks.apply_move_instructions(&solution.move_instructions, CrateStacks::move_many_from_top);
+            assert_eq!(crate_count(&crate_stacks), 30);
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<SupplyStacks>();
+    }
 }
\ No newline at end of file
@@ -1,6 +1,9 @@
 use std::collections::{HashSet, VecDeque};
-
-pub mod input;
+use std::fmt;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::debug;
 
 /// Response to the first and second parts
 /**
@@ -20,6 +23,7 @@ pub fn get_start_of_packet_position(characters: impl Iterator<Item=char>, window
         }
 
         let hash_set: HashSet<&char> = HashSet::from_iter(window.iter());
+        debug!(position = index + 1, window = %window.iter().collect::<String>(), distinct = hash_set.len(), "window");
 
         if hash_set.len() == window_size {
             return (index + 1) as isize;
@@ -28,6 +32,50 @@ pub fn get_start_of_packet_position(characters: impl Iterator<Item=char>, window
     -1
 }
 
+/// The datastream buffer received by the device. Any character is valid, so parsing can't fail.
+#[derive(PartialEq, Debug)]
+pub struct TuningTrouble {
+    characters: Vec<char>,
+}
+
+impl Solution for TuningTrouble {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self { characters: input.chars().collect() })
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(get_start_of_packet_position(self.characters.iter().copied(), 4).into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(get_start_of_packet_position(self.characters.iter().copied(), 14).into())
+    }
+}
+
+impl fmt::Display for TuningTrouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.characters.iter().try_for_each(|character| write!(f, "{}", character))
+    }
+}
+
+impl Generator for TuningTrouble {
+    /**
+        A datastream of size characters, 14 at least. It starts with letters drawn from only three
+        letters, which can't hold any marker, followed by a planted marker of 14 distinct letters at
+        a random position, followed by random letters.
+    */
+    fn generate(random: &mut Random, size: usize) -> String {
+        let size = size.max(14);
+        let mut letters: Vec<char> = ('a'..='z').collect();
+        letters.shuffle(random);
+        let marker_position = random.random_range(0..=size - 14);
+        let mut datastream: String = (0..marker_position).map(|_| letters[random.random_range(0..3)]).collect();
+        letters.shuffle(random);
+        datastream.extend(&letters[..14]);
+        datastream.extend((marker_position + 14..size).map(|_| letters[random.random_range(0..26)]));
+        datastream + "\n"
+    }
+}
 
 #[cfg(test)]
 mod test {
@@ -49,4 +97,29 @@ mod test {
         assert_eq!(get_start_of_packet_position("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg".chars(), 14), 29);
         assert_eq!(get_start_of_packet_position("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw".chars(), 14), 26);
     }
+
+    #[test]
+    fn test_tuning_trouble_solution() {
+        let tuning_trouble = TuningTrouble::parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap();
+        assert_eq!(tuning_trouble.part1().unwrap(), Answer::Number(7));
+        assert_eq!(tuning_trouble.part2().unwrap(), Answer::Number(19));
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = TuningTrouble::generate(&mut solution::random(seed), 100);
+            assert_eq!(input.trim_end().len(), 100);
+            let solution = TuningTrouble::parse(&input).unwrap();
+            let (Answer::Number(start_of_packet), Answer::Number(start_of_message)) = (solution.part1().unwrap(), solution.part2().unwrap()) else {
+                panic!("both answers are positions");
+            };
+            assert!(4 <= start_of_packet && start_of_packet <= start_of_message && start_of_message <= 100);
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<TuningTrouble>();
+    }
 }
\ No newline at end of file
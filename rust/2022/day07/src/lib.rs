@@ -1,21 +1,58 @@
-use std::cell::RefCell;
 use std::collections::hash_map::Values;
 use std::collections::HashMap;
-use std::rc::{Rc, Weak};
+use std::sync::{Arc, RwLock, Weak};
 use std::cmp;
 use std::ops::Deref;
+use std::{error, fmt};
+use parsing::error_at;
+use rand::Rng;
+use solution::{unknown_command, Answer, Error, Generator, Random, Repl, Solution};
+use tracing::{debug, trace};
 
-pub mod input;
-
-/**
+/*
     I decided to implement this exercise using polimorphism with enums. It is a bit tedious to
     pattern match a directory out of a DiskItem every single time I need to use it, but this
     pattern allows for a seamless use of size() and storage of files and directories in
     Directory.children. There are other ways of implementing this.
  */
 
-type DiskItemType = Rc<RefCell<DiskItem>>;
-type WeakDiskItemType = Weak<RefCell<DiskItem>>;
+type DiskItemType = Arc<RwLock<DiskItem>>;
+type WeakDiskItemType = Weak<RwLock<DiskItem>>;
+
+/**
+    Disk items are locked, rather than kept in RefCells, so that aoc can solve both parts of a parsed
+    file system at once on different threads. Only a panic while holding a lock could poison it.
+*/
+const UNPOISONED: &str = "no thread panics while holding a disk item";
+
+/// Errors found while parsing the terminal output. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    InvalidCommand { line: usize, column: usize, text: String },
+    InvalidListing { line: usize, column: usize, text: String },
+    InvalidFileSize { line: usize, column: usize, text: String },
+    UnknownDirectory { line: usize, column: usize, text: String },
+    NoParentDirectory { line: usize, column: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCommand { line, column, text } =>
+                write!(f, "line {}, column {}: expected `$ cd <directory>` or `$ ls`, found `{}`", line, column, text),
+            ParseError::InvalidListing { line, column, text } =>
+                write!(f, "line {}, column {}: expected `dir <name>` or `<size> <name>`, found `{}`", line, column, text),
+            ParseError::InvalidFileSize { line, column, text } =>
+                write!(f, "line {}, column {}: expected a file size, found `{}`", line, column, text),
+            ParseError::UnknownDirectory { line, column, text } =>
+                write!(f, "line {}, column {}: expected a directory listed before, found `{}`", line, column, text),
+            ParseError::NoParentDirectory { line, column, text } =>
+                write!(f, "line {}, column {}: expected a parent directory to exist for `{}`", line, column, text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
 
 /// Represents a directory in a DiskItem
 #[derive(Debug)]
@@ -30,7 +67,7 @@ impl Directory {
     }
 
     fn add_child(&mut self, name: String, item: DiskItem) {
-        self.children.insert(name, Rc::new(RefCell::new(item)));
+        self.children.insert(name, Arc::new(RwLock::new(item)));
     }
 
     fn get_child(&self, name: impl AsRef<str>) -> Option<&DiskItemType> {
@@ -42,7 +79,7 @@ impl Directory {
             .expect("Expect parent to exist since child hasn't been dropped yet"))
     }
 
-    fn directories(&self) -> DirectoryIterator {
+    fn directories(&self) -> DirectoryIterator<'_> {
         DirectoryIterator { disk_items: self.children.values() }
     }
 }
@@ -53,10 +90,17 @@ impl Directory {
 */
 impl PartialEq for Directory {
     fn eq(&self, other: &Directory) -> bool {
-        self.children == other.children
+        self.children.len() == other.children.len() && self.children.iter().all(|(name, child)| {
+            other.children.get(name).is_some_and(|other_child| same_disk_item(child, other_child))
+        })
     }
 }
 
+/// Compares what two disk items hold, since locks can't be compared
+fn same_disk_item(first: &DiskItemType, second: &DiskItemType) -> bool {
+    *first.read().expect(UNPOISONED) == *second.read().expect(UNPOISONED)
+}
+
 /**
     This iterator returns only directories out of a children attribute. It is backed by the
     std::collections::hash_map::Values iterator and skips over any values that aren't Directory.
@@ -75,7 +119,7 @@ impl <'a> Iterator for DirectoryIterator<'a> {
             match self.disk_items.next() {
                 None => return None,
                 Some(disk_item) =>
-                    if let DiskItem::Directory(_) = disk_item.borrow().deref() {
+                    if let DiskItem::Directory(_) = disk_item.read().expect(UNPOISONED).deref() {
                         return Some(disk_item)
                     }
             }
@@ -95,23 +139,23 @@ impl File {
     }
 }
 
+/// Sizes are None when they overflow, which the file sizes of a listing can make them do
 trait SizableDiskItem {
-    fn size(&self) -> usize;
+    fn size(&self) -> Option<usize>;
 }
 
 /// Gets the size of the current directory recursively
 impl SizableDiskItem for Directory {
-    fn size(&self) -> usize {
+    fn size(&self) -> Option<usize> {
         self.children
             .values()
-            .map(|item| item.borrow().size())
-            .sum()
+            .try_fold(0usize, |sum, item| sum.checked_add(item.read().expect(UNPOISONED).size()?))
     }
 }
 
 impl SizableDiskItem for File {
-    fn size(&self) -> usize {
-        self.size
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
     }
 }
 
@@ -130,7 +174,7 @@ pub enum DiskItem {
     the size of an item.
 */
 impl SizableDiskItem for DiskItem {
-    fn size(&self) -> usize {
+    fn size(&self) -> Option<usize> {
         match self {
             DiskItem::Directory(directory) => directory.size(),
             DiskItem::File(file) => file.size()
@@ -146,80 +190,73 @@ pub mod command_text_parser {
     use std::ops::DerefMut;
     use super::*;
 
-    pub fn parse(lines: impl Iterator<Item=String>) -> DiskItemType {
-        let root_directory = Rc::new(RefCell::new(
+    /// Blank lines are skipped. Every other line must be a cd or ls command or a listing entry.
+    pub fn parse(lines: impl Iterator<Item=String>) -> Result<DiskItemType, ParseError> {
+        let root_directory = Arc::new(RwLock::new(
             DiskItem::Directory(Directory::new(None))));
-        let mut current_directory: Option<DiskItemType> = Some(Rc::clone(&root_directory));
-
-        for line in lines {
-            if line.starts_with("$ cd") {
-                let directory_name = line
-                    .split(' ')
-                    .nth(2)
-                    .expect("There should be a directory name after cd");
-                current_directory = match directory_name {
-                    "/" => get_root_directory(&root_directory),
-                    ".." => get_parent_directory(current_directory),
-                    directory_name => get_directory_by_name(current_directory, directory_name)
-                };
-            } else if !line.starts_with("$ ls") {
-                let mut listing = line.split(' ');
-                match listing.next().expect("First position of listing should either be dir or file size") {
-                    "dir" => add_directory(&current_directory, listing
-                        .next()
-                        .expect("Second position of listing dir should be directory name")),
-                    file_size => add_file(&current_directory, listing
-                        .next()
-                        .expect("Second position of listing file should be directory name"),
-                        file_size),
+        let mut current_directory: DiskItemType = Arc::clone(&root_directory);
+
+        let lines: Vec<String> = lines.collect();
+        for line in parsing::lines(&lines) {
+            if line.is_empty() {
+                continue;
+            }
+            if let Ok([directory_name]) = line.pattern("$ cd {}") {
+                current_directory = match directory_name.as_str() {
+                    "/" => Arc::clone(&root_directory),
+                    ".." => get_parent_directory(&current_directory)
+                        .ok_or_else(|| error_at!(ParseError::NoParentDirectory)(line))?,
+                    name => get_directory_by_name(&current_directory, name)
+                        .ok_or_else(|| error_at!(ParseError::UnknownDirectory)(directory_name))?,
                 };
+                debug!(line = line.line(), directory = directory_name.as_str(), "changed directory");
+            } else if line.pattern::<0>("$ ls").is_ok() {
+                // The listing that follows belongs to the current directory
+            } else if line.as_str() == "$" || line.as_str().starts_with("$ ") {
+                return Err(error_at!(ParseError::InvalidCommand)(line));
+            } else if let Ok([directory_name]) = line.pattern("dir {}") {
+                trace!(line = line.line(), directory = directory_name.as_str(), "listed directory");
+                add_directory(&current_directory, directory_name.as_str());
+            } else if let Ok([file_size, file_name]) = line.pattern("{} {}") {
+                let file_size = file_size.integer::<usize>().map_err(error_at!(ParseError::InvalidFileSize))?;
+                trace!(line = line.line(), file = file_name.as_str(), size = file_size, "listed file");
+                add_file(&current_directory, file_name.as_str(), file_size);
+            } else {
+                return Err(error_at!(ParseError::InvalidListing)(line));
             }
         }
-        root_directory
-    }
-
-    fn get_root_directory(root_directory: &DiskItemType) -> Option<DiskItemType> {
-        Some(Rc::clone(root_directory))
+        Ok(root_directory)
     }
 
-    fn get_parent_directory(current_directory: Option<DiskItemType>) -> Option<DiskItemType> {
-        if let DiskItem::Directory(curr) = current_directory.unwrap().borrow().deref() {
-            curr.get_parent().as_ref().map(Rc::clone)
+    pub(crate) fn get_parent_directory(current_directory: &DiskItemType) -> Option<DiskItemType> {
+        if let DiskItem::Directory(curr) = current_directory.read().expect(UNPOISONED).deref() {
+            curr.get_parent().as_ref().map(Arc::clone)
         } else {
             None
         }
     }
 
-    fn get_directory_by_name(current_directory: Option<DiskItemType>, directory_name: &str) -> Option<DiskItemType> {
-        if let DiskItem::Directory(curr) = current_directory.unwrap().borrow().deref() {
-            curr.get_child(directory_name).map(Rc::clone)
+    /// Only returns directories, since changing into a file isn't possible
+    pub(crate) fn get_directory_by_name(current_directory: &DiskItemType, directory_name: &str) -> Option<DiskItemType> {
+        if let DiskItem::Directory(curr) = current_directory.read().expect(UNPOISONED).deref() {
+            curr.get_child(directory_name)
+                .filter(|child| matches!(child.read().expect(UNPOISONED).deref(), DiskItem::Directory(_)))
+                .map(Arc::clone)
         } else {
             None
         }
     }
 
-    fn add_directory(current_directory: &Option<DiskItemType>, directory_name: &str) {
-        if let DiskItem::Directory(directory) = current_directory
-            .as_ref()
-            .unwrap()
-            .borrow_mut()
-            .deref_mut() {
-                directory.add_child(directory_name.to_string(),
-                                    DiskItem::Directory(
-                                        Directory::new(
-                                            Some(Rc::downgrade(current_directory.as_ref().unwrap())))))
+    fn add_directory(current_directory: &DiskItemType, directory_name: &str) {
+        if let DiskItem::Directory(directory) = current_directory.write().expect(UNPOISONED).deref_mut() {
+            directory.add_child(directory_name.to_string(),
+                                DiskItem::Directory(Directory::new(Some(Arc::downgrade(current_directory)))))
         }
     }
 
-    fn add_file(current_directory: &Option<DiskItemType>, file_name: &str, file_size: &str) {
-        if let DiskItem::Directory(directory) = current_directory
-            .as_ref()
-            .unwrap()
-            .borrow_mut()
-            .deref_mut() {
-            directory.add_child(file_name.to_string(),
-                                DiskItem::File(File::new(file_size.parse::<usize>()
-                                        .expect("Expect file size to be usize"))))
+    fn add_file(current_directory: &DiskItemType, file_name: &str, file_size: usize) {
+        if let DiskItem::Directory(directory) = current_directory.write().expect(UNPOISONED).deref_mut() {
+            directory.add_child(file_name.to_string(), DiskItem::File(File::new(file_size)))
         }
     }
 
@@ -229,15 +266,15 @@ pub mod command_text_parser {
 
         #[test]
         fn test_parse_commands() {
-            let root_directory = Rc::new(RefCell::new(DiskItem::Directory(Directory::new(None))));
-            if let DiskItem::Directory(rtdir) = root_directory.borrow_mut().deref_mut() {
-                rtdir.add_child("a".to_string(), DiskItem::Directory(Directory::new(Some(Rc::downgrade(&root_directory)))));
+            let root_directory = Arc::new(RwLock::new(DiskItem::Directory(Directory::new(None))));
+            if let DiskItem::Directory(rtdir) = root_directory.write().expect(UNPOISONED).deref_mut() {
+                rtdir.add_child("a".to_string(), DiskItem::Directory(Directory::new(Some(Arc::downgrade(&root_directory)))));
                 rtdir.add_child("b.txt".to_string(), DiskItem::File(File::new(14_848_514)));
                 rtdir.add_child("c.dat".to_string(), DiskItem::File(File::new(8_504_156)));
-                rtdir.add_child("d".to_string(), DiskItem::Directory(Directory::new(Some(Rc::downgrade(&root_directory)))));
+                rtdir.add_child("d".to_string(), DiskItem::Directory(Directory::new(Some(Arc::downgrade(&root_directory)))));
                 let dir_a = rtdir.get_child("a").unwrap();
-                if let DiskItem::Directory(a) = dir_a.borrow_mut().deref_mut() {
-                    a.add_child("e".to_string(), DiskItem::Directory(Directory::new(Some(Rc::downgrade(dir_a)))));
+                if let DiskItem::Directory(a) = dir_a.write().expect(UNPOISONED).deref_mut() {
+                    a.add_child("e".to_string(), DiskItem::Directory(Directory::new(Some(Arc::downgrade(dir_a)))));
                     a.add_child("f".to_string(), DiskItem::File(File::new(29_116)));
                     a.add_child("g".to_string(), DiskItem::File(File::new(2_557)));
                     a.add_child("h.lst".to_string(), DiskItem::File(File::new(62_596)));
@@ -257,62 +294,333 @@ $ ls
 dir e
 29116 f
 2557 g
-62596 h.lst".lines().map(String::from)), root_directory);
+62596 h.lst".lines().map(String::from)).map(|root_directory| NoSpaceLeftOnDevice { root_directory }),
+                       Ok(NoSpaceLeftOnDevice { root_directory }));
+        }
+
+        #[test]
+        fn test_parse_commands_errors() {
+            let parse = |input: &str| parse(input.lines().map(String::from)).err();
+
+            assert_eq!(parse("$ cd /\n$ rm -rf"),
+                       Some(ParseError::InvalidCommand { line: 2, column: 1, text: "$ rm -rf".to_string() }));
+            assert_eq!(parse("$ ls\ndir"),
+                       Some(ParseError::InvalidListing { line: 2, column: 1, text: "dir".to_string() }));
+            assert_eq!(parse("$ ls\n12k a.txt"),
+                       Some(ParseError::InvalidFileSize { line: 2, column: 1, text: "12k".to_string() }));
+            assert_eq!(parse("$ ls\n12 a.txt\n$ cd a.txt"),
+                       Some(ParseError::UnknownDirectory { line: 3, column: 6, text: "a.txt".to_string() }));
+            assert_eq!(parse("$ cd /\n$ cd .."),
+                       Some(ParseError::NoParentDirectory { line: 2, column: 1, text: "$ cd ..".to_string() }));
+        }
+    }
+}
+
+/// Error of the parts when the file sizes add up to more than usize::MAX
+const SIZE_OVERFLOW: &str = "directory sizes overflow";
+
+/// The root directory rebuilt from the terminal output
+#[derive(Debug)]
+pub struct NoSpaceLeftOnDevice {
+    root_directory: DiskItemType,
+}
+
+impl PartialEq for NoSpaceLeftOnDevice {
+    fn eq(&self, other: &Self) -> bool {
+        same_disk_item(&self.root_directory, &other.root_directory)
+    }
+}
+
+impl Solution for NoSpaceLeftOnDevice {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self { root_directory: command_text_parser::parse(input.lines().map(String::from))? })
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(sum_directory_sizes_of(100_000, &self.root_directory).ok_or(SIZE_OVERFLOW)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(directory_size_to_free_of(30_000_000, &self.root_directory).ok_or(SIZE_OVERFLOW)?.into())
+    }
+}
+
+impl NoSpaceLeftOnDevice {
+    /// Renders the file system the way the puzzle statement draws it, children sorted by name
+    pub fn tree(&self) -> String {
+        let mut tree = String::new();
+        draw_tree(&mut tree, "/", self.root_directory.read().expect(UNPOISONED).deref(), 0);
+        tree
+    }
+}
+
+fn draw_tree(tree: &mut String, name: &str, item: &DiskItem, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match item {
+        DiskItem::Directory(directory) => {
+            tree.push_str(&format!("{}- {} (dir)\n", indent, name));
+            let mut children: Vec<(&String, &DiskItemType)> = directory.children.iter().collect();
+            children.sort_by_key(|(name, _)| *name);
+            for (name, child) in children {
+                draw_tree(tree, name, child.read().expect(UNPOISONED).deref(), depth + 1);
+            }
         }
+        DiskItem::File(file) => tree.push_str(&format!("{}- {} (file, size={})\n", indent, name, file.size)),
     }
 }
 
-/// Response to the first part
-pub fn sum_directory_sizes_of_100_000(lines: impl Iterator<Item=String>) -> usize {
-    sum_directory_sizes_of(100_000, &command_text_parser::parse(lines))
+/// Walks the parsed file system with cd, ls and du, see aoc repl
+pub struct NoSpaceLeftOnDeviceRepl {
+    root_directory: DiskItemType,
+    current_directory: DiskItemType,
+    path: Vec<String>,
 }
 
-fn sum_directory_sizes_of(max_size: usize, directory: &DiskItemType) -> usize {
-    if let DiskItem::Directory(current_directory) = directory.borrow().deref() {
-        let current_directory_size = current_directory.size();
+impl NoSpaceLeftOnDeviceRepl {
+    fn cd(&mut self, arguments: &[&str]) -> Result<String, Error> {
+        let [name] = arguments else {
+            return Err("expected `cd <directory>`".into());
+        };
+        let directory = match *name {
+            "/" => Some(Arc::clone(&self.root_directory)),
+            ".." => command_text_parser::get_parent_directory(&self.current_directory),
+            name => command_text_parser::get_directory_by_name(&self.current_directory, name),
+        };
+        self.current_directory = directory.ok_or_else(|| format!("no directory `{}` in {}", name, self.prompt()))?;
+        match *name {
+            "/" => self.path.clear(),
+            ".." => { self.path.pop(); }
+            name => self.path.push(name.to_string()),
+        }
+        Ok(String::new())
+    }
+
+    /// Children of the current directory sorted by name, as the puzzle's terminal lists them
+    fn ls(&self) -> String {
+        let current_directory = self.current_directory.read().expect(UNPOISONED);
+        let DiskItem::Directory(directory) = current_directory.deref() else {
+            unreachable!("cd only changes into directories")
+        };
+        let mut children: Vec<(&String, &DiskItemType)> = directory.children.iter().collect();
+        children.sort_by_key(|(name, _)| *name);
+        children
+            .into_iter()
+            .map(|(name, child)| match child.read().expect(UNPOISONED).deref() {
+                DiskItem::Directory(_) => format!("dir {}", name),
+                DiskItem::File(file) => format!("{} {}", file.size, name),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn du(&self, arguments: &[&str]) -> Result<String, Error> {
+        match arguments {
+            [] => Ok(self.current_directory.read().expect(UNPOISONED).size().ok_or(SIZE_OVERFLOW)?.to_string()),
+            [name] => match self.current_directory.read().expect(UNPOISONED).deref() {
+                DiskItem::Directory(directory) => directory
+                    .get_child(name)
+                    .ok_or_else(|| format!("no `{}` in {}", name, self.prompt()).into())
+                    .and_then(|child| Ok(child.read().expect(UNPOISONED).size().ok_or(SIZE_OVERFLOW)?.to_string())),
+                DiskItem::File(_) => unreachable!("cd only changes into directories"),
+            },
+            _ => Err("expected `du [name]`".into()),
+        }
+    }
+}
+
+impl Repl for NoSpaceLeftOnDeviceRepl {
+    fn start(input: &str) -> Result<Self, Error> {
+        let root_directory = NoSpaceLeftOnDevice::parse(input)?.root_directory;
+        Ok(Self { current_directory: Arc::clone(&root_directory), root_directory, path: vec![] })
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("cd <directory>", "changes into a child directory, the parent directory .. or the root directory /"),
+            ("ls", "lists the current directory"),
+            ("du [name]", "total size of the current directory, or of one of its children"),
+            ("tree", "draws the current directory and everything below it"),
+        ]
+    }
+
+    fn prompt(&self) -> String {
+        format!("/{}", self.path.join("/"))
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[&str]) -> Result<String, Error> {
+        match command {
+            "cd" => self.cd(arguments),
+            "ls" => Ok(self.ls()),
+            "du" => self.du(arguments),
+            "tree" => {
+                let mut tree = String::new();
+                draw_tree(&mut tree, self.path.last().map_or("/", String::as_str), self.current_directory.read().expect(UNPOISONED).deref(), 0);
+                Ok(tree)
+            }
+            _ => Err(unknown_command(command)),
+        }
+    }
+}
+
+/**
+    Renders a terminal transcript exploring every directory depth first, listing each one before
+    changing into its subdirectories. Children are sorted by name so that output is stable.
+*/
+impl fmt::Display for NoSpaceLeftOnDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn explore(f: &mut fmt::Formatter<'_>, directory: &Directory) -> fmt::Result {
+            let mut children: Vec<(&String, &DiskItemType)> = directory.children.iter().collect();
+            children.sort_by_key(|(name, _)| *name);
+            writeln!(f, "$ ls")?;
+            for (name, child) in &children {
+                match child.read().expect(UNPOISONED).deref() {
+                    DiskItem::Directory(_) => writeln!(f, "dir {}", name)?,
+                    DiskItem::File(file) => writeln!(f, "{} {}", file.size, name)?,
+                }
+            }
+            for (name, child) in &children {
+                if let DiskItem::Directory(child_directory) = child.read().expect(UNPOISONED).deref() {
+                    writeln!(f, "$ cd {}", name)?;
+                    explore(f, child_directory)?;
+                    writeln!(f, "$ cd ..")?;
+                }
+            }
+            Ok(())
+        }
+
+        writeln!(f, "$ cd /")?;
+        match self.root_directory.read().expect(UNPOISONED).deref() {
+            DiskItem::Directory(root_directory) => explore(f, root_directory),
+            DiskItem::File(_) => Ok(()),
+        }
+    }
+}
+
+/// Directory of a generated file system, children being indices of other directories
+struct GeneratedDirectory {
+    name: String,
+    directories: Vec<usize>,
+    files: Vec<(String, usize)>,
+}
+
+impl Generator for NoSpaceLeftOnDevice {
+    /**
+        A terminal transcript exploring size directories nested at random below /, each holding 1
+        to 4 files. File sizes add up to between 40,000,001 and 69,999,999 so that part 2 has some
+        space to free.
+    */
+    fn generate(random: &mut Random, size: usize) -> String {
+        let name = |random: &mut Random| -> String {
+            (0..random.random_range(1..=6)).map(|_| random.random_range('a'..='z')).collect()
+        };
+        let mut directories = vec![GeneratedDirectory { name: "/".to_string(), directories: vec![], files: vec![] }];
+        for index in 1..=size {
+            let parent = random.random_range(0..index);
+            directories[parent].directories.push(index);
+            directories.push(GeneratedDirectory { name: format!("{}{}", name(random), index), directories: vec![], files: vec![] });
+        }
+        let mut weights = vec![];
+        for parent in 0..directories.len() {
+            for index in 0..random.random_range(1..=4) {
+                let extension = ["", ".txt", ".dat", ".log"][random.random_range(0..4)];
+                // A file named like a directory next to it would replace it when parsed back
+                let file_name = loop {
+                    let file_name = format!("{}{}{}", name(random), index, extension);
+                    if directories[parent].directories.iter().all(|&child| directories[child].name != file_name) {
+                        break file_name;
+                    }
+                };
+                directories[parent].files.push((file_name, 0));
+                weights.push(random.random_range(1..=1_000));
+            }
+        }
+        let total_size: usize = random.random_range(40_000_001..70_000_000);
+        let total_weight: usize = weights.iter().sum();
+        let mut file_sizes = weights.into_iter().map(|weight| (weight * total_size / total_weight).max(1));
+        for directory in &mut directories {
+            for file in &mut directory.files {
+                file.1 = file_sizes.next().expect("one weight per file");
+            }
+        }
+
+        fn explore(directories: &[GeneratedDirectory], index: usize, transcript: &mut Vec<String>) {
+            transcript.push("$ ls".to_string());
+            for &child in &directories[index].directories {
+                transcript.push(format!("dir {}", directories[child].name));
+            }
+            for (file_name, file_size) in &directories[index].files {
+                transcript.push(format!("{} {}", file_size, file_name));
+            }
+            for &child in &directories[index].directories {
+                transcript.push(format!("$ cd {}", directories[child].name));
+                explore(directories, child, transcript);
+                transcript.push("$ cd ..".to_string());
+            }
+        }
+        let mut transcript = vec!["$ cd /".to_string()];
+        explore(&directories, 0, &mut transcript);
+        transcript.join("\n") + "\n"
+    }
+}
+
+/// Response to the first part, None when the sizes overflow
+pub fn sum_directory_sizes_of_100_000(lines: impl Iterator<Item=String>) -> Result<Option<usize>, ParseError> {
+    Ok(sum_directory_sizes_of(100_000, &command_text_parser::parse(lines)?))
+}
+
+fn sum_directory_sizes_of(max_size: usize, directory: &DiskItemType) -> Option<usize> {
+    if let DiskItem::Directory(current_directory) = directory.read().expect(UNPOISONED).deref() {
+        let current_directory_size = current_directory.size()?;
+        trace!(size = current_directory_size, max_size, "visiting directory");
         let current_directory_size = if current_directory_size <= max_size { current_directory_size } else { 0 };
 
         current_directory
             .directories()
-            .map(|directory| sum_directory_sizes_of(max_size, directory))
-            .sum::<usize>() + current_directory_size
+            .try_fold(current_directory_size, |sum, directory| sum.checked_add(sum_directory_sizes_of(max_size, directory)?))
     } else {
-        0
+        Some(0)
     }
 }
 
-/// Response to the second part
+/// Response to the second part, None when the sizes overflow
 /// Returns directory size to free necessary space or -1 in case space is already free
-pub fn directory_size_to_free_30_000_000(lines: impl Iterator<Item=String>) -> isize {
-    let directory = command_text_parser::parse(lines);
-    let min_size = 30_000_000 - (70_000_000 - directory.borrow().size() as isize);
-    if min_size >= 0 {
-        directory_size_to_free(min_size as usize, &directory) as isize
-    } else {
-        -1
+pub fn directory_size_to_free_30_000_000(lines: impl Iterator<Item=String>) -> Result<Option<isize>, ParseError> {
+    Ok(directory_size_to_free_of(30_000_000, &command_text_parser::parse(lines)?))
+}
+
+/// Returns directory size to free for an update of update_size or -1 in case space is already free
+fn directory_size_to_free_of(update_size: usize, directory: &DiskItemType) -> Option<isize> {
+    let required_space = directory.read().expect(UNPOISONED).size()?.checked_add(update_size)?;
+    debug!(update_size, required_space, "looking for the smallest directory to delete");
+    match required_space.checked_sub(70_000_000) {
+        Some(min_size) => isize::try_from(directory_size_to_free(min_size, directory)?).ok(),
+        None => Some(-1),
     }
 }
 
-fn directory_size_to_free(min_size: usize, directory: &DiskItemType) -> usize {
-    if let DiskItem::Directory(current_directory) = directory.borrow().deref() {
-        let current_directory_size = current_directory.size();
+fn directory_size_to_free(min_size: usize, directory: &DiskItemType) -> Option<usize> {
+    if let DiskItem::Directory(current_directory) = directory.read().expect(UNPOISONED).deref() {
+        let current_directory_size = current_directory.size()?;
+        trace!(size = current_directory_size, min_size, "visiting directory");
 
         let min = current_directory
             .directories()
             .map(|directory| directory_size_to_free(min_size, directory))
+            .collect::<Option<Vec<usize>>>()?
+            .into_iter()
             .filter(|size| *size > 0)
             .min()
             .unwrap_or(0);
 
         if current_directory_size >= min_size && min > 0 {
-            cmp::min(current_directory_size, min)
+            Some(cmp::min(current_directory_size, min))
         } else if current_directory_size >= min_size {
-            current_directory_size
+            Some(current_directory_size)
         } else {
-            0
+            Some(0)
         }
     } else {
-        0
+        Some(0)
     }
 }
 
@@ -329,7 +637,7 @@ mod test {
         sub_directory.add_child("b".to_string(), DiskItem::File(File::new(7)));
         root_directory.add_child("c".to_string(), DiskItem::Directory(sub_directory));
         root_directory.add_child("d".to_string(), DiskItem::File(File::new(70)));
-        assert_eq!(DiskItem::Directory(root_directory).size(), 200);
+        assert_eq!(DiskItem::Directory(root_directory).size(), Some(200));
     }
 
     #[test]
@@ -368,7 +676,53 @@ $ ls
 4060174 j
 8033020 d.log
 5626152 d.ext
-7214296 k".lines().map(String::from)), 95437);
+7214296 k".lines().map(String::from)), Ok(Some(95437)));
+    }
+
+    #[test]
+    fn test_tree_snapshot() {
+        let example = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+        snapshot::assert_snapshot!("tree_example", NoSpaceLeftOnDevice::parse(example).unwrap().tree());
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut repl = NoSpaceLeftOnDeviceRepl::start("$ cd /\n$ ls\ndir a\n14848514 b.txt\n$ cd a\n$ ls\ndir e\n29116 f\n$ cd e\n$ ls\n584 i").unwrap();
+        assert_eq!(repl.execute("ls", &[]).unwrap(), "dir a\n14848514 b.txt");
+        repl.execute("cd", &["a"]).unwrap();
+        repl.execute("cd", &["e"]).unwrap();
+        assert_eq!(repl.prompt(), "/a/e");
+        repl.execute("cd", &[".."]).unwrap();
+        assert_eq!((repl.prompt(), repl.execute("du", &[]).unwrap()), ("/a".to_string(), "29700".to_string()));
+        assert_eq!(repl.execute("du", &["f"]).unwrap(), "29116");
+        assert_eq!(repl.execute("tree", &[]).unwrap(), "- a (dir)\n  - e (dir)\n    - i (file, size=584)\n  - f (file, size=29116)\n");
+        assert_eq!(repl.execute("cd", &["f"]).unwrap_err().to_string(), "no directory `f` in /a");
+        repl.execute("cd", &["/"]).unwrap();
+        assert_eq!(repl.execute("cd", &[".."]).unwrap_err().to_string(), "no directory `..` in /");
+        assert_eq!(repl.execute("du", &[]).unwrap(), "14878214");
     }
 
     #[test]
@@ -396,6 +750,63 @@ $ ls
 4060174 j
 8033020 d.log
 5626152 d.ext
-7214296 k".lines().map(String::from)), 24933642);
+7214296 k".lines().map(String::from)), Ok(Some(24933642)));
+    }
+
+    #[test]
+    fn test_no_space_left_on_device_solution() {
+        let no_space_left_on_device = NoSpaceLeftOnDevice::parse("\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k").unwrap();
+        assert_eq!(no_space_left_on_device.part1().unwrap(), Answer::Number(95437));
+        assert_eq!(no_space_left_on_device.part2().unwrap(), Answer::Number(24933642));
+    }
+
+    #[test]
+    fn test_size_overflow() {
+        let two_files = NoSpaceLeftOnDevice::parse("$ cd /\n$ ls\n9223372036854775807 a\n9223372036854775807 b").unwrap();
+        assert_eq!(two_files.part1().unwrap(), Answer::Number(0));
+        assert_eq!(two_files.part2().unwrap_err().to_string(), "directory sizes overflow");
+
+        let three_files = NoSpaceLeftOnDevice::parse("$ cd /\n$ ls\n9223372036854775807 a\n9223372036854775807 b\n9223372036854775807 c").unwrap();
+        assert_eq!(three_files.part1().unwrap_err().to_string(), "directory sizes overflow");
+        assert_eq!(three_files.part2().unwrap_err().to_string(), "directory sizes overflow");
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = NoSpaceLeftOnDevice::generate(&mut solution::random(seed), 30);
+            let solution = NoSpaceLeftOnDevice::parse(&input).unwrap();
+            let used_space = solution.root_directory.read().expect(UNPOISONED).size().unwrap();
+            assert!((40_000_001..70_000_000).contains(&used_space), "{}", used_space);
+            assert!(matches!(solution.part2().unwrap(), Answer::Number(size) if size > 0));
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<NoSpaceLeftOnDevice>();
     }
 }
\ No newline at end of file
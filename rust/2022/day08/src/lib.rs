@@ -1,20 +1,35 @@
-use std::collections::HashSet;
-
-pub mod input;
-
-/// A TreePatch is a square of numbers representing tree heights
-type TreePatch = Vec<Vec<u8>>;
-
-/// Structure storing the x/y position of a tree in a TreePatch
-#[derive(PartialEq, Eq, Hash)]
-pub struct Tree(usize, usize);
+use std::{error, fmt};
+use grid::{Direction, Grid, Position};
+use rand::Rng;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::debug;
+
+/// Errors found while parsing the tree patch. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    EmptyTreePatch,
+    InvalidHeight { line: usize, column: usize, text: String },
+    InconsistentRowLength { line: usize, column: usize, text: String },
+}
 
-impl Tree {
-    fn new(x: usize, y: usize) -> Self {
-        Self (x, y)
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EmptyTreePatch =>
+                write!(f, "expected at least one row of tree heights"),
+            ParseError::InvalidHeight { line, column, text } =>
+                write!(f, "line {}, column {}: expected a tree height from 0 to 9, found `{}`", line, column, text),
+            ParseError::InconsistentRowLength { line, column, text } =>
+                write!(f, "line {}, column {}: expected as many trees as the first row, found `{}`", line, column, text),
+        }
     }
 }
 
+impl error::Error for ParseError {}
+
+/// A TreePatch is a square of numbers representing tree heights
+type TreePatch = Grid<u8>;
+
 /**
     A dedicated module to parsing the input and providing the model for the algorithm to work with.
     There could be other methods in this module and other modules dedicated to generating the same
@@ -22,14 +37,14 @@ impl Tree {
 */
 mod tree_patch_parser {
     use super::*;
-    pub fn parse_from_text(lines: impl Iterator<Item=String>) -> TreePatch {
-        lines
-            .map(|line|
-                line
-                    .chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect())
-            .collect()
+
+    /// Every row must have the same number of trees, and there must be at least one tree
+    pub fn parse_from_text(lines: impl Iterator<Item=String>) -> Result<TreePatch, ParseError> {
+        Grid::parse(lines, |c| c.to_digit(10).map(|height| height as u8)).map_err(|err| match err {
+            grid::ParseError::Empty => ParseError::EmptyTreePatch,
+            grid::ParseError::InvalidCell { line, column, text } => ParseError::InvalidHeight { line, column, text },
+            grid::ParseError::InconsistentRowLength { line, column, text } => ParseError::InconsistentRowLength { line, column, text },
+        })
     }
 }
 
@@ -40,78 +55,19 @@ mod tree_viewer {
     pub mod outside_in {
         use super::*;
 
-        /**
-            Computes all visible trees from the outside. Groups visible trees from each side in a
-            hashset to remove duplicates.
-        */
-        pub fn get_visible_trees_count(tree_patch: &TreePatch) -> usize {
-            let mut visible_trees = HashSet::new();
-
-            for y in 0..tree_patch.len() {
-                visible_trees.extend(from_left(y, tree_patch));
-                visible_trees.extend(from_right(y, tree_patch));
-            }
-            for x in 0..tree_patch[0].len() {
-                visible_trees.extend(from_top(x, tree_patch));
-                visible_trees.extend(from_bottom(x, tree_patch));
-            }
-            visible_trees.len()
+        /// Whether each tree is taller than every tree between it and some edge of the patch
+        pub fn get_visibility(tree_patch: &TreePatch) -> Grid<bool> {
+            Grid::from_fn(tree_patch.width(), tree_patch.height(), |position| {
+                let visible = Direction::ALL.into_iter().any(|direction| {
+                    tree_patch.walk(position, direction).all(|(_, &other)| other < tree_patch[position])
+                });
+                debug!(?position, height = tree_patch[position], visible, "scanned tree");
+                visible
+            })
         }
 
-        fn from_left(y: usize, tree_patch: &TreePatch) -> Vec<Tree> {
-            let line_size = tree_patch[0].len();
-            let mut tallest_tree = tree_patch[y][0];
-            let mut visible_trees: Vec<Tree> = vec![];
-            visible_trees.push(Tree::new(0, y));
-            for x in 0..line_size {
-                if tree_patch[y][x] > tallest_tree {
-                    tallest_tree = tree_patch[y][x];
-                    visible_trees.push(Tree::new(x, y));
-                }
-            }
-            visible_trees
-        }
-
-        fn from_right(y: usize, tree_patch: &TreePatch) -> Vec<Tree> {
-            let line_size = tree_patch[0].len();
-            let last_x = line_size - 1;
-            let mut tallest_tree = tree_patch[y][last_x];
-            let mut visible_trees: Vec<Tree> = vec![];
-            visible_trees.push(Tree::new(last_x, y));
-            for x in (0..line_size).rev() {
-                if tree_patch[y][x] > tallest_tree {
-                    tallest_tree = tree_patch[y][x];
-                    visible_trees.push(Tree::new(x, y));
-                }
-            }
-            visible_trees
-        }
-
-        fn from_top(x: usize, tree_patch: &TreePatch) -> Vec<Tree> {
-            let mut tallest_tree = tree_patch[0][x];
-            let mut visible_trees: Vec<Tree> = vec![];
-            visible_trees.push(Tree::new(x, 0));
-            for y in 0..tree_patch.len() {
-                if tree_patch[y][x] > tallest_tree {
-                    tallest_tree = tree_patch[y][x];
-                    visible_trees.push(Tree::new(x, y));
-                }
-            }
-            visible_trees
-        }
-
-        fn from_bottom(x: usize, tree_patch: &TreePatch) -> Vec<Tree> {
-            let last_y = tree_patch.len() - 1;
-            let mut tallest_tree = tree_patch[last_y][x];
-            let mut visible_trees: Vec<Tree> = vec![];
-            visible_trees.push(Tree::new(x, last_y));
-            for y in (0..tree_patch.len()).rev() {
-                if tree_patch[y][x] > tallest_tree {
-                    tallest_tree = tree_patch[y][x];
-                    visible_trees.push(Tree::new(x, y));
-                }
-            }
-            visible_trees
+        pub fn get_visible_trees_count(tree_patch: &TreePatch) -> usize {
+            get_visibility(tree_patch).cells().filter(|&(_, &visible)| visible).count()
         }
     }
 
@@ -120,83 +76,86 @@ mod tree_viewer {
 
         /// Computes scenic score for each tree in the TreePatch matrix and picks out the heighest
         pub fn get_highest_scenic_score(tree_patch: &TreePatch) -> usize {
-            let mut highest_score = 0;
-            for y in 0..tree_patch.len() {
-                for x in 0..tree_patch[0].len() {
-                    let score = get_scenic_score(x, y, tree_patch);
-                    if score > highest_score {
-                        highest_score = score;
-                    }
-                }
-            }
-            highest_score
+            tree_patch.positions().map(|position| get_scenic_score(position, tree_patch)).max().unwrap_or(0)
         }
 
-        fn get_scenic_score(x: usize, y: usize, tree_patch: &TreePatch) -> usize {
-            to_left(x, y, tree_patch)
-                * to_right(x, y, tree_patch)
-                * to_top(x, y, tree_patch)
-                * to_bottom(x, y, tree_patch)
+        fn get_scenic_score(position: Position, tree_patch: &TreePatch) -> usize {
+            Direction::ALL.into_iter().map(|direction| viewing_distance(position, direction, tree_patch)).product()
         }
 
-        fn to_left(x: usize, y: usize, tree_patch: &TreePatch) -> usize {
-            let tree_in_consideration = tree_patch[y][x];
+        /// Number of trees seen from position towards direction, up to the first one at least as tall
+        fn viewing_distance(position: Position, direction: Direction, tree_patch: &TreePatch) -> usize {
+            let tree_in_consideration = tree_patch[position];
             let mut score = 0;
-            for i in (0..x).rev() {
+            for (_, &height) in tree_patch.walk(position, direction) {
                 score += 1;
-                if tree_patch[y][i] >= tree_in_consideration {
-                    return score;
+                if height >= tree_in_consideration {
+                    break;
                 }
             }
+            debug!(?position, ?direction, distance = score, "scanned direction");
             score
         }
+    }
+}
 
-        fn to_right(x: usize, y: usize, tree_patch: &TreePatch) -> usize {
-            let tree_in_consideration = tree_patch[y][x];
-            let mut score = 0;
-            for i in (x + 1)..tree_patch[0].len() {
-                score += 1;
-                if tree_patch[y][i] >= tree_in_consideration {
-                    return score;
-                }
-            }
-            score
-        }
+/// The tree patch parsed from the map of tree heights
+#[derive(PartialEq, Debug)]
+pub struct TreetopTreeHouse {
+    tree_patch: TreePatch,
+}
 
-        fn to_top(x: usize, y: usize, tree_patch: &TreePatch) -> usize {
-            let tree_in_consideration = tree_patch[y][x];
-            let mut score = 0;
-            for i in (0..y).rev() {
-                score += 1;
-                if tree_patch[i][x] >= tree_in_consideration {
-                    return score;
-                }
+impl TreetopTreeHouse {
+    /// The tree patch where trees hidden from every edge are replaced with dots
+    pub fn visibility_map(&self) -> String {
+        let visibility = tree_viewer::outside_in::get_visibility(&self.tree_patch);
+        Grid::from_fn(self.tree_patch.width(), self.tree_patch.height(), |position| {
+            match visibility[position] {
+                true => char::from(b'0' + self.tree_patch[position]),
+                false => '.',
             }
-            score
-        }
+        }).to_string()
+    }
+}
 
-        fn to_bottom(x: usize, y: usize, tree_patch: &TreePatch) -> usize {
-            let tree_in_consideration = tree_patch[y][x];
-            let mut score = 0;
-            for i in (y + 1)..tree_patch.len() {
-                score += 1;
-                if tree_patch[i][x] >= tree_in_consideration {
-                    return score;
-                }
-            }
-            score
-        }
+impl Solution for TreetopTreeHouse {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self { tree_patch: tree_patch_parser::parse_from_text(input.lines().map(String::from))? })
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(tree_viewer::outside_in::get_visible_trees_count(&self.tree_patch).into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(tree_viewer::inside_out::get_highest_scenic_score(&self.tree_patch).into())
+    }
+}
+
+impl fmt::Display for TreetopTreeHouse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tree_patch)
+    }
+}
+
+impl Generator for TreetopTreeHouse {
+    /// A square patch of size by size trees, one at least, with random heights
+    fn generate(random: &mut Random, size: usize) -> String {
+        let size = size.max(1);
+        (0..size)
+            .map(|_| (0..size).map(|_| random.random_range('0'..='9')).chain(['\n']).collect::<String>())
+            .collect()
     }
 }
 
 /// Response to the first part
-pub fn get_visible_trees(lines: impl Iterator<Item=String>) -> usize {
-    tree_viewer::outside_in::get_visible_trees_count(&tree_patch_parser::parse_from_text(lines))
+pub fn get_visible_trees(lines: impl Iterator<Item=String>) -> Result<usize, ParseError> {
+    Ok(tree_viewer::outside_in::get_visible_trees_count(&tree_patch_parser::parse_from_text(lines)?))
 }
 
 /// Response to the second part
-pub fn get_highest_scenic_score(lines: impl Iterator<Item=String>) -> usize {
-    tree_viewer::inside_out::get_highest_scenic_score(&tree_patch_parser::parse_from_text(lines))
+pub fn get_highest_scenic_score(lines: impl Iterator<Item=String>) -> Result<usize, ParseError> {
+    Ok(tree_viewer::inside_out::get_highest_scenic_score(&tree_patch_parser::parse_from_text(lines)?))
 }
 
 
@@ -211,7 +170,7 @@ mod test {
 25512
 65332
 33549
-35390".lines().map(String::from)), 21);
+35390".lines().map(String::from)), Ok(21));
     }
 
     #[test]
@@ -221,6 +180,47 @@ mod test {
 25512
 65332
 33549
-35390".lines().map(String::from)), 8);
+35390".lines().map(String::from)), Ok(8));
+    }
+
+    #[test]
+    fn test_treetop_tree_house_solution() {
+        let treetop_tree_house = TreetopTreeHouse::parse("\
+30373
+25512
+65332
+33549
+35390").unwrap();
+        assert_eq!(treetop_tree_house.part1().unwrap(), Answer::Number(21));
+        assert_eq!(treetop_tree_house.part2().unwrap(), Answer::Number(8));
+    }
+
+    #[test]
+    fn test_visibility_map_snapshot() {
+        let treetop_tree_house = TreetopTreeHouse::parse("30373\n25512\n65332\n33549\n35390\n").unwrap();
+        snapshot::assert_snapshot!("visibility_example", treetop_tree_house.visibility_map());
+    }
+
+    #[test]
+    fn test_get_visible_trees_errors() {
+        assert_eq!(get_visible_trees("".lines().map(String::from)), Err(ParseError::EmptyTreePatch));
+        assert_eq!(get_visible_trees("303\n2a5".lines().map(String::from)),
+                   Err(ParseError::InvalidHeight { line: 2, column: 2, text: "a".to_string() }));
+        assert_eq!(get_visible_trees("303\n25".lines().map(String::from)),
+                   Err(ParseError::InconsistentRowLength { line: 2, column: 1, text: "25".to_string() }));
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = TreetopTreeHouse::generate(&mut solution::random(seed), 15);
+            assert_eq!(input.lines().count(), 15);
+            assert!(input.lines().all(|line| line.len() == 15));
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<TreetopTreeHouse>();
     }
 }
\ No newline at end of file
@@ -1,20 +1,37 @@
 use std::collections::HashSet;
+use std::{error, fmt};
+use geometry::{Direction, Point};
+use parsing::{error_at, Token};
+use rand::Rng;
+use solution::{Answer, Error, Generator, Random, Solution};
+use tracing::{debug, debug_span, trace};
 
-pub mod input;
-
-/// Simple clonable structure to hold the coordinates of knots
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
-struct Point<T> {
-    x: T,
-    y: T,
+/// Errors found while parsing head motions. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    MissingSeparator { line: usize, column: usize, text: String },
+    UnknownDirection { line: usize, column: usize, text: String },
+    InvalidSteps { line: usize, column: usize, text: String },
 }
 
-impl<T> Point<T> {
-    fn new(x: T, y: T) -> Self {
-        Self { x, y }
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingSeparator { line, column, text } =>
+                write!(f, "line {}, column {}: expected `<U|D|L|R> <steps>`, found `{}`", line, column, text),
+            ParseError::UnknownDirection { line, column, text } =>
+                write!(f, "line {}, column {}: expected direction U, D, L or R, found `{}`", line, column, text),
+            ParseError::InvalidSteps { line, column, text } =>
+                write!(f, "line {}, column {}: expected a number of steps up to {}, found `{}`", line, column, MAX_STEPS, text),
+        }
     }
 }
 
+impl error::Error for ParseError {}
+
+/// Steps of a single motion, puzzle inputs stay far below it. The parts walk every step one by one.
+pub const MAX_STEPS: usize = 1_000;
+
 struct Rope {
     knots: Vec<Point<isize>>,
     unique_tail_positions: HashSet<Point<isize>>,
@@ -22,10 +39,10 @@ struct Rope {
 
 impl Rope {
     pub fn new(knots: usize) -> Self {
-        // Preallocate all knots in the knots array, all at position x=0 y=0
+        // Preallocate all knots in the knots array, all at the origin
         Self {
-            knots: (0..knots).map(|_| Point::new(0, 0)).collect(),
-            unique_tail_positions: HashSet::from([Point::new(0, 0)]),
+            knots: vec![Point::ORIGIN; knots],
+            unique_tail_positions: HashSet::from([Point::ORIGIN]),
         }
     }
 
@@ -33,23 +50,8 @@ impl Rope {
         self.unique_tail_positions.len()
     }
 
-    fn up(&mut self) {
-        self.knots[0].y += 1;
-        self.update_knots();
-    }
-
-    fn down(&mut self) {
-        self.knots[0].y -= 1;
-        self.update_knots();
-    }
-
-    fn left(&mut self) {
-        self.knots[0].x -= 1;
-        self.update_knots();
-    }
-
-    fn right(&mut self) {
-        self.knots[0].x += 1;
+    fn move_head(&mut self, direction: Direction) {
+        self.knots[0] += direction.vector();
         self.update_knots();
     }
 
@@ -57,12 +59,11 @@ impl Rope {
         let knots_len = self.knots.len();
         for next_knot_index in 1..knots_len {
             let previous_knot = self.knots[next_knot_index - 1];
-            let mut current_knot = &mut self.knots[next_knot_index];
+            let current_knot = &mut self.knots[next_knot_index];
 
-            let x_spread = previous_knot.x - current_knot.x;
-            let y_spread = previous_knot.y - current_knot.y;
+            let spread = previous_knot - *current_knot;
 
-            if isize::abs(x_spread) > 1 || isize::abs(y_spread) > 1 {
+            if spread.chebyshev() > 1 {
                 /*
                     Head and tail are not adjacent at this point. Tail will need to move.
                     If they're not adjacent, one of the spreads is 2 and the other is either 0 or 1.
@@ -70,12 +71,12 @@ impl Rope {
                     1, then we have to move diagonally, meaning moving both x and y by a combination of
                     -1 and/or 1.
                  */
-                current_knot.x += x_spread.signum();
-                current_knot.y += y_spread.signum();
+                *current_knot += spread.signum();
+                trace!(knot = next_knot_index, position = %current_knot, "knot moved");
 
                 if next_knot_index + 1 == knots_len {
                     // Only store the unique position of the tail/last knot
-                    self.unique_tail_positions.insert(current_knot.clone());
+                    self.unique_tail_positions.insert(*current_knot);
                 }
             } else {
                 // If the current knot didn't move, the ones behind it certainly won't either
@@ -85,6 +86,7 @@ impl Rope {
     }
 }
 
+#[derive(PartialEq, Debug)]
 pub enum Command {
     UP(usize),
     DOWN(usize),
@@ -95,41 +97,101 @@ pub enum Command {
 pub mod parse_command_text {
     use super::*;
 
-    pub fn parse_line(line: &str) -> Command {
-        let (command, n) = line.split_once(' ')
-            .expect("Expect there to be a space separating the command from the number of occurrences");
-        let n = n.parse().expect("Expect number of occurrences to be a valid number");
-        match command {
-            "U" => Command::UP(n),
-            "D" => Command::DOWN(n),
-            "L" => Command::LEFT(n),
-            "R" => Command::RIGHT(n),
-            _ => panic!("Unknown command")
+    /// line_number is only used for error reporting
+    pub fn parse_line(line_number: usize, line: &str) -> Result<Command, ParseError> {
+        let (command, n) = Token::new(line_number, line).key_value().map_err(error_at!(ParseError::MissingSeparator))?;
+        let n = n.integer().ok().filter(|n| *n <= MAX_STEPS).ok_or_else(|| error_at!(ParseError::InvalidSteps)(n))?;
+        match command.as_str() {
+            "U" => Ok(Command::UP(n)),
+            "D" => Ok(Command::DOWN(n)),
+            "L" => Ok(Command::LEFT(n)),
+            "R" => Ok(Command::RIGHT(n)),
+            _ => Err(error_at!(ParseError::UnknownDirection)(command))
+        }
+    }
+}
+
+/// Every motion of the head of the rope
+#[derive(PartialEq, Debug)]
+pub struct RopeBridge {
+    commands: Vec<Command>,
+}
+
+impl RopeBridge {
+    pub fn from_lines(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<Self, ParseError> {
+        Ok(Self {
+            commands: lines
+                .enumerate()
+                .map(|(index, line)| parse_command_text::parse_line(index + 1, line.as_ref()))
+                .collect::<Result<_, _>>()?
+        })
+    }
+}
+
+impl Solution for RopeBridge {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines())?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(count_unique_tail_positions(&self.commands, 2).into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(count_unique_tail_positions(&self.commands, 10).into())
+    }
+}
+
+impl fmt::Display for RopeBridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in &self.commands {
+            match command {
+                Command::UP(n) => writeln!(f, "U {}", n)?,
+                Command::DOWN(n) => writeln!(f, "D {}", n)?,
+                Command::LEFT(n) => writeln!(f, "L {}", n)?,
+                Command::RIGHT(n) => writeln!(f, "R {}", n)?,
+            }
         }
+        Ok(())
+    }
+}
+
+impl Generator for RopeBridge {
+    /// size head motions of 1 to 20 steps in any direction
+    fn generate(random: &mut Random, size: usize) -> String {
+        (0..size)
+            .map(|_| format!("{} {}\n", ["U", "D", "L", "R"][random.random_range(0..4)], random.random_range(1..=20)))
+            .collect()
     }
 }
 
-fn count_unique_tail_positions(lines: impl Iterator<Item=String>, knots: usize) -> usize {
+fn count_unique_tail_positions(commands: &[Command], knots: usize) -> usize {
     let mut rope = Rope::new(knots);
-    for line in lines {
-        match parse_command_text::parse_line(&line) {
-            Command::UP(n) => for _ in 0..n { rope.up() }
-            Command::DOWN(n) => for _ in 0..n { rope.down() }
-            Command::LEFT(n) => for _ in 0..n { rope.left() }
-            Command::RIGHT(n) => for _ in 0..n { rope.right() }
+    for command in commands {
+        let (direction, n) = match *command {
+            Command::UP(n) => (Direction::Up, n),
+            Command::DOWN(n) => (Direction::Down, n),
+            Command::LEFT(n) => (Direction::Left, n),
+            Command::RIGHT(n) => (Direction::Right, n),
+        };
+        let _span = debug_span!("move", ?direction, steps = n).entered();
+        for _ in 0..n {
+            rope.move_head(direction);
         }
+        debug!(head = %rope.knots[0], tail = %rope.knots[knots - 1],
+               unique_tail_positions = rope.get_unique_tail_positions_count(), "moved");
     }
     rope.get_unique_tail_positions_count()
 }
 
 /// Response to the first part
-pub fn count_unique_tail_positions_with_2_knots(lines: impl Iterator<Item=String>) -> usize {
-    count_unique_tail_positions(lines, 2)
+pub fn count_unique_tail_positions_with_2_knots(lines: impl Iterator<Item=String>) -> Result<usize, ParseError> {
+    Ok(count_unique_tail_positions(&RopeBridge::from_lines(lines)?.commands, 2))
 }
 
 /// Response to the second part
-pub fn count_unique_tail_positions_with_10_knots(lines: impl Iterator<Item=String>) -> usize {
-    count_unique_tail_positions(lines, 10)
+pub fn count_unique_tail_positions_with_10_knots(lines: impl Iterator<Item=String>) -> Result<usize, ParseError> {
+    Ok(count_unique_tail_positions(&RopeBridge::from_lines(lines)?.commands, 10))
 }
 
 #[cfg(test)]
@@ -146,7 +208,7 @@ D 1
 R 4
 D 1
 L 5
-R 2".lines().map(String::from)), 13);
+R 2".lines().map(String::from)), Ok(13));
     }
 
     #[test]
@@ -159,7 +221,7 @@ D 1
 R 4
 D 1
 L 5
-R 2".lines().map(String::from)), 1);
+R 2".lines().map(String::from)), Ok(1));
     }
 
     #[test]
@@ -172,6 +234,44 @@ D 3
 R 17
 D 10
 L 25
-U 20".lines().map(String::from)), 36);
+U 20".lines().map(String::from)), Ok(36));
+    }
+
+    #[test]
+    fn test_rope_bridge_solution() {
+        let rope_bridge = RopeBridge::parse("\
+R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2").unwrap();
+        assert_eq!(rope_bridge.part1().unwrap(), Answer::Number(13));
+        assert_eq!(rope_bridge.part2().unwrap(), Answer::Number(1));
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert_eq!(parse_command_text::parse_line(7, "R4").err(),
+                   Some(ParseError::MissingSeparator { line: 7, column: 1, text: "R4".to_string() }));
+        assert_eq!(parse_command_text::parse_line(7, "X 4").err(),
+                   Some(ParseError::UnknownDirection { line: 7, column: 1, text: "X".to_string() }));
+        assert_eq!(parse_command_text::parse_line(7, "R -4").err(),
+                   Some(ParseError::InvalidSteps { line: 7, column: 3, text: "-4".to_string() }));
+        assert_eq!(parse_command_text::parse_line(1, "R 1001").err(),
+                   Some(ParseError::InvalidSteps { line: 1, column: 3, text: "1001".to_string() }));
+        assert_eq!(parse_command_text::parse_line(1, "R 1000"), Ok(Command::RIGHT(1000)));
+    }
+
+    #[test]
+    fn test_generator() {
+        assert_eq!(RopeBridge::generate(&mut solution::random(1), 50).lines().count(), 50);
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<RopeBridge>();
     }
 }
\ No newline at end of file
@@ -1,5 +1,31 @@
-pub mod input;
+use std::{error, fmt};
+use grid::Grid;
+use parsing::{error_at, Token};
+use rand::Rng;
+use solution::{count_argument, unknown_command, Answer, Error, Generator, Random, Repl, Solution};
+use tracing::{debug, debug_span, trace};
 
+/// Errors found while parsing the program. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    UnknownInstruction { line: usize, column: usize, text: String },
+    InvalidAddxValue { line: usize, column: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownInstruction { line, column, text } =>
+                write!(f, "line {}, column {}: expected `addx V` or `noop`, found `{}`", line, column, text),
+            ParseError::InvalidAddxValue { line, column, text } =>
+                write!(f, "line {}, column {}: expected addx to be followed by a 32-bit number, found `{}`", line, column, text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[derive(PartialEq, Debug)]
 pub enum Command {
     ADDX(isize),
     NOOP
@@ -13,11 +39,18 @@ pub enum Command {
 mod command_text_parser {
     use super::*;
 
-    pub fn parse_line(line: &str) -> Command {
-        match line.split_once(' ') {
-            Some((_, n)) => Command::ADDX(n.parse()
-                .expect("Expect addx command to be followed by a number")),
-            None => Command::NOOP
+    /**
+        line_number is only used for error reporting. addx values are limited to 32 bits so that
+        neither the register nor the signal strengths it's multiplied into can overflow an isize.
+    */
+    pub fn parse_line(line_number: usize, line: &str) -> Result<Command, ParseError> {
+        let line = Token::new(line_number, line);
+        if let Ok([n]) = line.pattern("addx {}") {
+            Ok(Command::ADDX(n.integer::<i32>().map_err(error_at!(ParseError::InvalidAddxValue))? as isize))
+        } else if line.pattern::<0>("noop").is_ok() {
+            Ok(Command::NOOP)
+        } else {
+            Err(error_at!(ParseError::UnknownInstruction)(line))
         }
     }
 }
@@ -43,27 +76,172 @@ impl<F: FnMut(usize, isize)> SimpleCpu<F> {
     }
     
     pub fn addx(&mut self, n: isize) {
+        let _span = debug_span!("addx", n, cycle = self.cycle + 1).entered();
         self.tick();
         self.tick();
         self.register_x += n;
+        debug!(cycle = self.cycle, x = self.register_x, "executed");
     }
     
     pub fn noop(&mut self) {
+        let _span = debug_span!("noop", cycle = self.cycle + 1).entered();
         self.tick();
+        debug!(cycle = self.cycle, x = self.register_x, "executed");
     }
     
+    /// Traces every cycle, with the value the register holds during it
     fn tick(&mut self) {
         self.cycle += 1;
+        trace!(cycle = self.cycle, x = self.register_x, "tick");
         (self.interrupt)(self.cycle, self.register_x);
     }
 
     pub fn get_cycle(&self) -> usize {
         self.cycle
     }
+
+    pub fn get_register_x(&self) -> isize {
+        self.register_x
+    }
+}
+
+/// The program run by the CPU
+#[derive(PartialEq, Debug)]
+pub struct CathodeRayTube {
+    commands: Vec<Command>,
+}
+
+impl CathodeRayTube {
+    pub fn from_lines(lines: impl Iterator<Item=impl AsRef<str>>) -> Result<Self, ParseError> {
+        Ok(Self {
+            commands: lines
+                .enumerate()
+                .map(|(index, line)| command_text_parser::parse_line(index + 1, line.as_ref()))
+                .collect::<Result<_, _>>()?
+        })
+    }
+}
+
+impl Solution for CathodeRayTube {
+    fn parse(input: &str) -> Result<Self, Error> {
+        Ok(Self::from_lines(input.lines())?)
+    }
+
+    fn part1(&self) -> Result<Answer, Error> {
+        Ok(sum_signal_strengths_at_6_intervals(&self.commands).into())
+    }
+
+    fn part2(&self) -> Result<Answer, Error> {
+        Ok(crt_output(&self.commands).into())
+    }
+}
+
+impl fmt::Display for CathodeRayTube {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in &self.commands {
+            match command {
+                Command::ADDX(n) => writeln!(f, "addx {}", n)?,
+                Command::NOOP => writeln!(f, "noop")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Steps the SimpleCpu through the program while drawing the screen, see aoc repl
+pub struct CathodeRayTubeRepl {
+    commands: Vec<Command>,
+    executed_commands: usize,
+    simple_cpu: SimpleCpu<fn(usize, isize)>,
+    screen: Grid<char>,
+}
+
+impl CathodeRayTubeRepl {
+    fn reset(&mut self) {
+        self.executed_commands = 0;
+        self.simple_cpu = SimpleCpu::from_interrupt(|_, _| {});
+        self.screen = Grid::new(40, 6, '.');
+    }
+
+    /// Executes up to count of the remaining instructions, returning one line per instruction
+    fn step(&mut self, count: usize) -> String {
+        let end = self.executed_commands.saturating_add(count).min(self.commands.len());
+        let mut output = Vec::new();
+        for command in &self.commands[self.executed_commands..end] {
+            // The register only changes once an instruction completes, so it's the same during all its cycles
+            let (first_cycle, register_x) = (self.simple_cpu.get_cycle() + 1, self.simple_cpu.get_register_x());
+            let instruction = match *command {
+                Command::ADDX(n) => { self.simple_cpu.addx(n); format!("addx {}", n) }
+                Command::NOOP => { self.simple_cpu.noop(); "noop".to_string() }
+            };
+            for cycle in first_cycle..=self.simple_cpu.get_cycle() {
+                draw_pixel(&mut self.screen, cycle, register_x);
+            }
+            output.push(format!("cycle {}: {}, x = {}", first_cycle, instruction, self.simple_cpu.get_register_x()));
+        }
+        self.executed_commands = end;
+        output.join("\n")
+    }
+}
+
+impl Repl for CathodeRayTubeRepl {
+    fn start(input: &str) -> Result<Self, Error> {
+        let commands = CathodeRayTube::parse(input)?.commands;
+        Ok(Self { commands, executed_commands: 0, simple_cpu: SimpleCpu::from_interrupt(|_, _| {}), screen: Grid::new(40, 6, '.') })
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("step [n]", "executes the next n instructions, 1 by default"),
+            ("run", "executes the rest of the program"),
+            ("x", "value of the register X"),
+            ("screen", "the CRT as drawn so far"),
+            ("reset", "restarts the program on a dark screen"),
+        ]
+    }
+
+    fn prompt(&self) -> String {
+        format!("cycle {}, instruction {}/{}", self.simple_cpu.get_cycle(), self.executed_commands, self.commands.len())
+    }
+
+    fn execute(&mut self, command: &str, arguments: &[&str]) -> Result<String, Error> {
+        match command {
+            "step" => Ok(self.step(count_argument(arguments)?)),
+            "run" => Ok(self.step(usize::MAX)),
+            "x" => Ok(self.simple_cpu.get_register_x().to_string()),
+            "screen" => Ok(self.screen.to_string()),
+            "reset" => {
+                self.reset();
+                Ok(String::new())
+            }
+            _ => Err(unknown_command(command)),
+        }
+    }
+}
+
+impl Generator for CathodeRayTube {
+    /// A program of size instructions, a third of them noop and the others addx -10 to 10
+    fn generate(random: &mut Random, size: usize) -> String {
+        (0..size)
+            .map(|_| match random.random_range(0..3) {
+                0 => "noop\n".to_string(),
+                _ => format!("addx {}\n", random.random_range(-10..=10)),
+            })
+            .collect()
+    }
 }
 
 /// Response to the first part
-pub fn get_sum_signal_strengths_at_6_intervals(lines: impl Iterator<Item=String>) -> isize {
+pub fn get_sum_signal_strengths_at_6_intervals(lines: impl Iterator<Item=String>) -> Result<isize, ParseError> {
+    Ok(sum_signal_strengths_at_6_intervals(&CathodeRayTube::from_lines(lines)?.commands))
+}
+
+/// Response to the second part
+pub fn render_crt_output(lines: impl Iterator<Item=String>) -> Result<String, ParseError> {
+    Ok(crt_output(&CathodeRayTube::from_lines(lines)?.commands))
+}
+
+fn sum_signal_strengths_at_6_intervals(commands: &[Command]) -> isize {
     let mut signal_strength_sum = 0;
     let mut simple_cpu = SimpleCpu::from_interrupt(|cycle, register_x| {
         if cycle == 20 || cycle == 60 || cycle == 100 || cycle == 140 || cycle == 180
@@ -71,8 +249,8 @@ pub fn get_sum_signal_strengths_at_6_intervals(lines: impl Iterator<Item=String>
             signal_strength_sum += cycle as isize * register_x;
         }
     });
-    for line in lines {
-        match command_text_parser::parse_line(&line) {
+    for command in commands {
+        match *command {
             Command::ADDX(n) => simple_cpu.addx(n),
             Command::NOOP => simple_cpu.noop()
         }
@@ -84,38 +262,40 @@ pub fn get_sum_signal_strengths_at_6_intervals(lines: impl Iterator<Item=String>
     signal_strength_sum
 }
 
-/// Response to the second part
-pub fn render_crt_output(lines: impl Iterator<Item=String>) -> String {
-    let mut crt_output = String::new();
-    let mut simple_cpu = SimpleCpu::from_interrupt(|cycle, register_x| {
-        /*
-            Cycle goes from 0 to 39. We're decrementing cycle here because in the problem
-            statement cycle 1 refers to the first pixel while register_x 0 refers to the first
-            pixel. We need to either decrement cycle or increment register_x and the former seems
-            less cumbersome and more in line wiht the domain of the problem.
-         */
-        let cycle = (cycle - 1) % 40;
-        if register_x - 1 <= cycle as isize && register_x + 1 >= cycle as isize {
-            crt_output.push_str("#");
-        } else {
-            crt_output.push_str(".");
-        }
-        if cycle == 39 {
-            crt_output.push_str("\n");
-        }
-    });
+/// Renders the 40x6 screen, whose pixels stay dark past the end of the program
+fn crt_output(commands: &[Command]) -> String {
+    let mut screen = Grid::new(40, 6, '.');
+    let mut simple_cpu = SimpleCpu::from_interrupt(|cycle, register_x| draw_pixel(&mut screen, cycle, register_x));
 
-    for line in lines {
-        match command_text_parser::parse_line(&line) {
+    for command in commands {
+        match *command {
             Command::ADDX(n) => simple_cpu.addx(n),
             Command::NOOP => simple_cpu.noop()
         }
         // stop computation sooner since we only need 240 cycles
-        if simple_cpu.get_cycle() == 240 {
+        if simple_cpu.get_cycle() >= 240 {
             break;
         }
     }
-    crt_output
+    screen.to_string()
+}
+
+/// Lights the pixel drawn during cycle if the sprite centered on register_x covers it
+fn draw_pixel(screen: &mut Grid<char>, cycle: usize, register_x: isize) {
+    /*
+        x goes from 0 to 39. We're decrementing cycle here because in the problem
+        statement cycle 1 refers to the first pixel while register_x 0 refers to the first
+        pixel. We need to either decrement cycle or increment register_x and the former seems
+        less cumbersome and more in line wiht the domain of the problem.
+     */
+    if cycle == 0 || cycle > 240 {
+        // addx may run past the screen's last pixel
+        return;
+    }
+    let (x, y) = ((cycle - 1) % 40, (cycle - 1) / 40);
+    if register_x - 1 <= x as isize && register_x + 1 >= x as isize {
+        screen[(x, y)] = '#';
+    }
 }
 
 #[cfg(test)]
@@ -272,18 +452,66 @@ noop";
 
     #[test]
     fn test_get_sum_signal_strengths_at_6_intervals() {
-        assert_eq!(get_sum_signal_strengths_at_6_intervals(INPUT.lines().map(String::from)), 13140);
+        assert_eq!(get_sum_signal_strengths_at_6_intervals(INPUT.lines().map(String::from)), Ok(13140));
     }
 
     #[test]
     fn test_render_crt_output() {
-        assert_eq!(render_crt_output(INPUT.lines().map(String::from)), "\
-##..##..##..##..##..##..##..##..##..##..
-###...###...###...###...###...###...###.
-####....####....####....####....####....
-#####.....#####.....#####.....#####.....
-######......######......######......####
-#######.......#######.......#######.....
-".to_string());
+        snapshot::assert_snapshot!("crt_example", render_crt_output(INPUT.lines().map(String::from)).unwrap());
+    }
+
+    #[test]
+    fn test_render_crt_output_short_program() {
+        let screen = render_crt_output(["addx 5".to_string()].into_iter()).unwrap();
+        let dark = ".".repeat(40);
+        assert_eq!(screen, format!("##..{}\n{}", &dark[4..], format!("{}\n", dark).repeat(5)));
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut repl = CathodeRayTubeRepl::start("noop\naddx 3\naddx -5\n").unwrap();
+        assert_eq!(repl.execute("step", &[]).unwrap(), "cycle 1: noop, x = 1");
+        assert_eq!(repl.execute("step", &["5"]).unwrap(), "cycle 2: addx 3, x = 4\ncycle 4: addx -5, x = -1");
+        assert_eq!((repl.prompt(), repl.execute("x", &[]).unwrap()), ("cycle 5, instruction 3/3".to_string(), "-1".to_string()));
+        assert!(repl.execute("screen", &[]).unwrap().starts_with("#####....."));
+        repl.execute("reset", &[]).unwrap();
+        assert_eq!(repl.prompt(), "cycle 0, instruction 0/3");
+        assert!(repl.execute("step", &["x"]).is_err());
+    }
+
+    #[test]
+    fn test_cathode_ray_tube_solution() {
+        let cathode_ray_tube = CathodeRayTube::parse(INPUT).unwrap();
+        assert_eq!(cathode_ray_tube.part1().unwrap(), Answer::Number(13140));
+        assert_eq!(cathode_ray_tube.part2().unwrap(), Answer::from(render_crt_output(INPUT.lines().map(String::from)).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert_eq!(command_text_parser::parse_line(3, "addx").err(),
+                   Some(ParseError::UnknownInstruction { line: 3, column: 1, text: "addx".to_string() }));
+        assert_eq!(command_text_parser::parse_line(3, "subx 1").err(),
+                   Some(ParseError::UnknownInstruction { line: 3, column: 1, text: "subx 1".to_string() }));
+        assert_eq!(command_text_parser::parse_line(3, "addx one").err(),
+                   Some(ParseError::InvalidAddxValue { line: 3, column: 6, text: "one".to_string() }));
+        assert_eq!(command_text_parser::parse_line(3, "addx 2147483648").err(),
+                   Some(ParseError::InvalidAddxValue { line: 3, column: 6, text: "2147483648".to_string() }));
+        assert_eq!(command_text_parser::parse_line(1, "addx -9223372036854775807").err(),
+                   Some(ParseError::InvalidAddxValue { line: 1, column: 6, text: "-9223372036854775807".to_string() }));
+        assert_eq!(command_text_parser::parse_line(3, "addx -2147483648"), Ok(Command::ADDX(-2147483648)));
+    }
+
+    #[test]
+    fn test_generator() {
+        for seed in 0..20 {
+            let input = CathodeRayTube::generate(&mut solution::random(seed), 200);
+            assert_eq!(input.lines().count(), 200);
+            assert_eq!(CathodeRayTube::parse(&input).unwrap().part2().unwrap().to_string().lines().count(), 6);
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        solution::check_parser::<CathodeRayTube>();
     }
 }
\ No newline at end of file
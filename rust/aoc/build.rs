@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/**
+    Hashes the sources of every workspace member, and the lock file pinning their dependencies, into
+    AOC_SOLVER_VERSION: the crate version followed by the first 16 hexadecimal digits of the hash,
+    e.g. 0.1.0+3f2c1e0d9a8b7c6d. The answer cache keys answers by it, so that they always belong to
+    the code compiled into this very binary.
+*/
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo"));
+    let root = manifest_dir.parent().expect("aoc lies in the workspace directory");
+    let manifest = root.join("Cargo.toml");
+    let workspace: toml::Table = fs::read_to_string(&manifest)
+        .expect("the workspace has a Cargo.toml")
+        .parse()
+        .expect("the workspace Cargo.toml is valid");
+    let members = workspace["workspace"]["members"].as_array().expect("the workspace lists its members");
+
+    let mut files = vec![manifest, root.join("Cargo.lock")];
+    for member in members.iter().filter_map(|member| member.as_str()) {
+        let member = root.join(member);
+        files.push(member.join("Cargo.toml"));
+        println!("cargo:rerun-if-changed={}", member.join("src").display());
+        source_files(&member.join("src"), &mut files);
+    }
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in &files {
+        println!("cargo:rerun-if-changed={}", file.display());
+        // A missing Cargo.lock is hashed as empty, it's then created by this very build
+        let contents = fs::read(file).unwrap_or_default();
+        hasher.update(file.strip_prefix(root).unwrap_or(file).to_string_lossy().as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    let hash = format!("{:x}", hasher.finalize());
+    println!("cargo:rustc-env=AOC_SOLVER_VERSION={}+{}", env!("CARGO_PKG_VERSION"), &hash[..16]);
+}
+
+/// Every file below dir, recursively
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            source_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
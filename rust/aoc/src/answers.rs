@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use solution::{Answer, Error};
+
+/// Expected value of a part, written either as a TOML integer or string
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+enum Expected {
+    Number(i64),
+    Text(String),
+}
+
+impl From<Expected> for Answer {
+    fn from(expected: Expected) -> Self {
+        match expected {
+            Expected::Number(n) => Answer::Number(n.into()),
+            Expected::Text(text) => Answer::Text(text),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+struct DayAnswers {
+    part1: Option<Expected>,
+    part2: Option<Expected>,
+}
+
+impl DayAnswers {
+    fn expected(&self, part: u8) -> Option<Answer> {
+        let expected = match part {
+            1 => self.part1.as_ref(),
+            2 => self.part2.as_ref(),
+            _ => None,
+        };
+        expected.cloned().map(Answer::from)
+    }
+}
+
+/**
+    Accepted answers of a year, stored in <year>/answers.toml as one [dayNN] table per day with
+    optional part1 and part2 keys. Multi-line answers such as day 10's CRT use TOML multi-line
+    strings.
+*/
+#[derive(Default, Debug)]
+pub struct Answers {
+    days: BTreeMap<String, DayAnswers>,
+}
+
+impl Answers {
+    /// Location of the answers file of a year
+    pub fn path(year: u16) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(year.to_string())
+            .join("answers.toml")
+    }
+
+    /// Reads the answers file of a year. A missing file simply holds no answers.
+    pub fn load(year: u16) -> Result<Self, Error> {
+        Self::load_file(&Self::path(year))
+    }
+
+    /// Reads an answers file. A missing file simply holds no answers.
+    pub fn load_file(path: &Path) -> Result<Self, Error> {
+        load_toml(path, Self::from_toml)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        Ok(Self { days: toml::from_str(text)? })
+    }
+
+    /// Expected answer of a part (1 or 2) of a day, if known
+    pub fn expected(&self, day: u8, part: u8) -> Option<Answer> {
+        self.days.get(&format!("day{:02}", day))?.expected(part)
+    }
+}
+
+/// Extension of the sidecar files holding the expected answers of the input next to them
+pub const SIDECAR_EXTENSION: &str = "answers";
+
+/**
+    Expected answers of a single input, such as a team member's, stored next to it in a sidecar
+    file named after the whole input file name: alice.txt is checked against alice.txt.answers.
+    It holds optional top-level part1 and part2 keys, without any [dayNN] table header.
+*/
+#[derive(Default, Debug)]
+pub struct Sidecar {
+    answers: DayAnswers,
+}
+
+impl Sidecar {
+    /// Location of the sidecar of an input file, its name followed by the sidecar extension
+    pub fn path(input: &Path) -> PathBuf {
+        let mut name = input.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(SIDECAR_EXTENSION);
+        input.with_file_name(name)
+    }
+
+    pub fn is_sidecar(path: &Path) -> bool {
+        path.extension().is_some_and(|extension| extension == SIDECAR_EXTENSION)
+    }
+
+    /// Reads the sidecar of an input file. A missing sidecar simply holds no answers.
+    pub fn load(input: &Path) -> Result<Self, Error> {
+        load_toml(&Self::path(input), Self::from_toml)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        Ok(Self { answers: toml::from_str(text)? })
+    }
+
+    /// Expected answer of a part (1 or 2), if known
+    pub fn expected(&self, part: u8) -> Option<Answer> {
+        self.answers.expected(part)
+    }
+}
+
+/// Parses a TOML file, a missing one being the default value
+fn load_toml<T: Default>(path: &Path, parse: fn(&str) -> Result<T, Error>) -> Result<T, Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse(&text).map_err(|err| format!("{}: {}", path.display(), err).into()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(err) => Err(format!("{}: {}", path.display(), err).into()),
+    }
+}
+
+/// Outcome of checking a part against its expected answer
+#[derive(Debug)]
+pub enum Verdict {
+    Pass,
+    Mismatch { expected: String, actual: String },
+    Unknown(String),
+    Failed(Error),
+}
+
+impl Verdict {
+    /// Answers are compared through their text, so 42 and "42" are the same answer
+    pub fn new(expected: Option<Answer>, actual: Result<Answer, Error>) -> Self {
+        match (expected, actual) {
+            (_, Err(err)) => Verdict::Failed(err),
+            (None, Ok(actual)) => Verdict::Unknown(actual.to_string()),
+            (Some(expected), Ok(actual)) => {
+                let (expected, actual) = (expected.to_string(), actual.to_string());
+                if expected == actual {
+                    Verdict::Pass
+                } else {
+                    Verdict::Mismatch { expected, actual }
+                }
+            }
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Mismatch { .. } | Verdict::Failed(_))
+    }
+}
+
+/// Line by line diff of two multi-line answers, shared with the snapshot tests of rendered outputs
+pub use snapshot::diff;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ANSWERS: &str = "[day05]\npart1 = \"CMZ\"\n\n[day06]\npart1 = 7\npart2 = \"19\"\n\n[day10]\npart2 = \"\"\"\n##..\n#..#\n\"\"\"\n";
+
+    #[test]
+    fn test_expected() {
+        let answers = Answers::from_toml(ANSWERS).unwrap();
+        assert_eq!(answers.expected(5, 1), Some(Answer::from("CMZ")));
+        assert_eq!(answers.expected(5, 2), None);
+        assert_eq!(answers.expected(6, 1), Some(Answer::Number(7)));
+        assert_eq!(answers.expected(10, 2), Some(Answer::from("##..\n#..#\n")));
+        assert_eq!(answers.expected(1, 1), None);
+        assert!(Answers::from_toml("[day01]\npart3 = 1\n").is_err());
+    }
+
+    #[test]
+    fn test_sidecar() {
+        let sidecar = Sidecar::from_toml("part1 = 24000\npart2 = \"45000\"\n").unwrap();
+        assert_eq!(sidecar.expected(1), Some(Answer::Number(24000)));
+        assert_eq!(sidecar.expected(2), Some(Answer::from("45000")));
+        assert_eq!(Sidecar::default().expected(1), None);
+        assert!(Sidecar::from_toml("[day01]\npart1 = 1\n").is_err());
+        assert_eq!(Sidecar::path(Path::new("inputs/alice.txt")), Path::new("inputs/alice.txt.answers"));
+        assert_eq!(Sidecar::path(Path::new("inputs/alice")), Path::new("inputs/alice.answers"));
+        assert!(Sidecar::is_sidecar(Path::new("inputs/alice.txt.answers")));
+        assert!(!Sidecar::is_sidecar(Path::new("inputs/alice")));
+    }
+
+    #[test]
+    fn test_verdict() {
+        let answers = Answers::from_toml(ANSWERS).unwrap();
+        assert!(matches!(Verdict::new(answers.expected(6, 2), Ok(Answer::Number(19))), Verdict::Pass));
+        assert!(matches!(Verdict::new(answers.expected(6, 1), Ok(Answer::Number(8))), Verdict::Mismatch { .. }));
+        assert!(matches!(Verdict::new(answers.expected(1, 1), Ok(Answer::Number(8))), Verdict::Unknown(_)));
+        assert!(Verdict::new(answers.expected(6, 1), Err("oops".into())).is_failure());
+        assert!(!Verdict::new(None, Ok(Answer::Number(8))).is_failure());
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff("##..\n#..#\n", "##..\n#..#\n"), Vec::<String>::new());
+        assert_eq!(diff("##..\n#..#\n", "##..\n#.##\n.\n"), vec!["  2 - #..#", "  2 + #.##", "  3 + ."]);
+    }
+
+    #[test]
+    fn test_answers_files_parse() {
+        assert!(Answers::path(2022).is_file());
+        assert!(Answers::load(2022).unwrap().expected(10, 2).is_some());
+        assert!(Answers::load(1999).unwrap().expected(1, 1).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use solution::{Answer, Error};
+
+use crate::answers::{self, Sidecar, Verdict};
+use crate::bench;
+use crate::registry::Entry;
+use crate::report::{self, Record};
+
+/// Outcome of one part against one input of a batch, with its verdict against the input's sidecar
+pub struct Row {
+    pub record: Record,
+    pub verdict: Verdict,
+}
+
+/// Input files directly inside dir, sorted by name, leaving out sidecars, hidden files and directories
+pub fn inputs(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut inputs = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|err| format!("{}: {}", dir.display(), err))? {
+        let path = entry.map_err(|err| format!("{}: {}", dir.display(), err))?.path();
+        let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if path.is_file() && !hidden && !Sidecar::is_sidecar(&path) {
+            inputs.push(path);
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/**
+    Runs a day against every input on jobs threads and checks each answer against the sidecar of
+    its input. Rows follow the order of inputs, then parts.
+*/
+pub fn run(entry: &Entry, inputs: &[PathBuf], jobs: usize) -> Vec<Row> {
+    let days: Vec<(&Entry, PathBuf)> = inputs.iter().map(|input| (entry, input.clone())).collect();
+    report::run_parallel(&days, jobs, None, |_| {})
+        .into_iter()
+        .map(|record| Row { verdict: verdict(&record), record })
+        .collect()
+}
+
+/// Verdict of a record against its input's sidecar. An unreadable sidecar fails the record.
+fn verdict(record: &Record) -> Verdict {
+    let (part, actual) = match (record.part, &record.answer, &record.error) {
+        (_, _, Some(err)) => return Verdict::Failed(err.as_str().into()),
+        (Some(part), Some(answer), None) => (part, Answer::from(answer.as_str())),
+        _ => unreachable!("records hold either an answer to a part or an error"),
+    };
+    match Sidecar::load(Path::new(&record.input)) {
+        Ok(sidecar) => Verdict::new(sidecar.expected(part), Ok(actual)),
+        Err(err) => Verdict::Failed(err),
+    }
+}
+
+/**
+    Table of rows with the input's file name, the status of the part and its answer or error.
+    Statuses are pass, FAIL for answers differing from the sidecar's, FAILED for errors and - for
+    parts without expected answer. Mismatches are then detailed, multi-line answers as a line diff,
+    followed by the counts of each status.
+*/
+pub fn table(rows: &[Row]) -> String {
+    let name = |row: &Row| {
+        let input = Path::new(&row.record.input);
+        input.file_name().map_or_else(|| row.record.input.clone(), |name| name.to_string_lossy().into_owned())
+    };
+    let text = |row: &Row| match &row.verdict {
+        Verdict::Failed(err) => err.to_string(),
+        _ => row.record.answer.clone().unwrap_or_default(),
+    };
+    let input_width = rows.iter().map(|row| name(row).chars().count()).max().unwrap_or(0).max("input".len());
+    let answer_width = rows.iter()
+        .flat_map(|row| text(row).lines().map(|line| line.chars().count()).collect::<Vec<usize>>())
+        .max()
+        .unwrap_or(0)
+        .max("answer".len());
+    let mut table = format!("{:<iw$} {:<4} {:<6} {:<aw$} {:>10}\n", "input", "part", "status", "answer", "time",
+                            iw = input_width, aw = answer_width);
+    for row in rows {
+        let part = row.record.part.map_or("-".to_string(), |part| part.to_string());
+        let status = match row.verdict {
+            Verdict::Pass => "pass",
+            Verdict::Mismatch { .. } => "FAIL",
+            Verdict::Unknown(_) => "-",
+            Verdict::Failed(_) => "FAILED",
+        };
+        let time = row.record.duration_ns.map_or("-".to_string(), bench::format_nanos);
+        let text = text(row);
+        let mut lines = text.lines();
+        let first = lines.next().unwrap_or_default();
+        table.push_str(&format!("{:<iw$} {:<4} {:<6} {:<aw$} {:>10}\n", name(row), part, status, first, time,
+                                iw = input_width, aw = answer_width));
+        for line in lines {
+            table.push_str(&format!("{:<width$}{}\n", "", line, width = input_width + 13));
+        }
+    }
+    for row in rows {
+        let Verdict::Mismatch { expected, actual } = &row.verdict else { continue };
+        let label = format!("{} part {}", name(row), row.record.part.unwrap_or_default());
+        if expected.contains('\n') || actual.contains('\n') {
+            table.push_str(&format!("{}: expected (-) and found (+) lines:\n", label));
+            for line in answers::diff(expected, actual) {
+                table.push_str(&format!("    {}\n", line));
+            }
+        } else {
+            table.push_str(&format!("{}: expected {}, found {}\n", label, expected, actual));
+        }
+    }
+    let count = |matches: fn(&Verdict) -> bool| rows.iter().filter(|row| matches(&row.verdict)).count();
+    table.push_str(&format!("{} parts, {} passed, {} failed, {} without expected answer\n", rows.len(),
+                            count(|verdict| matches!(verdict, Verdict::Pass)), count(Verdict::is_failure),
+                            count(|verdict| matches!(verdict, Verdict::Unknown(_)))));
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+    use crate::registry;
+
+    #[test]
+    fn test_inputs() {
+        let dir = temp_dir("batch-inputs");
+        for name in ["bob", "alice.txt", "alice.txt.answers", ".hidden"] {
+            fs::write(dir.join(name), "1\n").unwrap();
+        }
+        fs::create_dir(dir.join("carol")).unwrap();
+        assert_eq!(inputs(&dir).unwrap(), vec![dir.join("alice.txt"), dir.join("bob")]);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(inputs(&dir).is_err());
+    }
+
+    #[test]
+    fn test_run_and_table() {
+        let dir = temp_dir("batch-run");
+        fs::write(dir.join("alice.txt"), "1000\n2000\n\n3000\n").unwrap();
+        fs::write(dir.join("alice.txt.answers"), "part1 = 3000\npart2 = \"6000\"\n").unwrap();
+        fs::write(dir.join("bob.txt"), "4000\n").unwrap();
+        fs::write(dir.join("bob.txt.answers"), "part1 = 4001\n").unwrap();
+        fs::write(dir.join("carol.txt"), "x\n").unwrap();
+        fs::write(dir.join("dave.txt"), "7\n").unwrap();
+        fs::write(dir.join("dave.txt.answers"), "part3 = 7\n").unwrap();
+        let inputs = inputs(&dir).unwrap();
+        let rows = run(registry::find(2022, 1).unwrap(), &inputs, 2);
+        let statuses: Vec<String> = rows.iter().map(|row| format!("{:?}", row.verdict)).collect();
+        assert!(matches!(rows[0].verdict, Verdict::Pass) && matches!(rows[1].verdict, Verdict::Pass), "{:?}", statuses);
+        assert!(matches!(rows[2].verdict, Verdict::Mismatch { .. }), "{:?}", statuses);
+        assert!(matches!(rows[3].verdict, Verdict::Unknown(_)), "{:?}", statuses);
+        assert!(matches!(rows[4].verdict, Verdict::Failed(_)) && rows[4].record.part.is_none(), "{:?}", statuses);
+        assert!(rows[5..].iter().all(|row| matches!(row.verdict, Verdict::Failed(_))), "{:?}", statuses);
+
+        let table = table(&rows);
+        let lines: Vec<&str> = table.lines().map(str::trim_end).collect();
+        assert!(lines[0].starts_with("input     part status answer "), "{}", table);
+        assert!(lines[1].starts_with("alice.txt 1    pass   3000 "), "{}", table);
+        assert!(lines[3].starts_with("bob.txt   1    FAIL   4000 "), "{}", table);
+        assert!(lines[4].starts_with("bob.txt   2    -      4000 "), "{}", table);
+        assert!(lines[5].starts_with("carol.txt -    FAILED cannot parse input"), "{}", table);
+        assert!(lines[6].contains(&format!("FAILED {}: TOML parse error", Sidecar::path(&dir.join("dave.txt")).display())), "{}", table);
+        assert!(table.contains("unknown field `part3`"), "{}", table);
+        assert_eq!(&lines[lines.len() - 2..], ["bob.txt part 1: expected 4001, found 4000", "7 parts, 2 passed, 4 failed, 1 without expected answer"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_table_diffs_multi_line_answers() {
+        let entry = registry::find(2022, 10).unwrap();
+        let record = report::run(entry, Path::new("missing.txt"), None).remove(0);
+        let record = Record { part: Some(2), answer: Some("#.\n##\n".to_string()), error: None, ..record };
+        let rows = [Row { record, verdict: Verdict::new(Some("#.\n.#\n".into()), Ok("#.\n##\n".into())) }];
+        let table = table(&rows);
+        let lines: Vec<&str> = table.lines().map(str::trim_end).collect();
+        assert_eq!(&lines[1..], [
+            "missing.txt 2    FAIL   #.              -",
+            "                        ##",
+            "missing.txt part 2: expected (-) and found (+) lines:",
+            "      2 - .#",
+            "      2 + ##",
+            "1 parts, 0 passed, 1 failed, 0 without expected answer",
+        ]);
+    }
+}
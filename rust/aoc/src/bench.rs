@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::hint;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use solution::Error;
+
+use crate::registry::Entry;
+
+/// Part of a day's run timed separately
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part1 => write!(f, "part1"),
+            Phase::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+/// Summary of the samples of a phase, in nanoseconds so that it can be stored in a baseline file
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Stats {
+    pub min: u64,
+    pub median: u64,
+    pub p95: u64,
+}
+
+impl Stats {
+    /// Summarizes samples with the nearest rank method. Returns None if there are no samples.
+    pub fn new(samples: &[Duration]) -> Option<Self> {
+        let mut nanos: Vec<u64> = samples.iter().map(|sample| sample.as_nanos() as u64).collect();
+        nanos.sort_unstable();
+        let percentile = |p: usize| nanos[(nanos.len() * p).div_ceil(100).saturating_sub(1)];
+        Some(Self { min: *nanos.first()?, median: percentile(50), p95: percentile(95) })
+    }
+}
+
+/// How many times each phase is run before and while being measured
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    pub warm_up: usize,
+    pub samples: usize,
+}
+
+/// Calls f warm_up times, then returns the duration of each of the next samples calls
+fn sample<T>(settings: Settings, mut f: impl FnMut() -> T) -> Vec<Duration> {
+    for _ in 0..settings.warm_up {
+        hint::black_box(f());
+    }
+    (0..settings.samples)
+        .map(|_| {
+            let start = Instant::now();
+            hint::black_box(f());
+            start.elapsed()
+        })
+        .collect()
+}
+
+/**
+    Times parsing and both parts of a day against the given input. Parsing is measured by parsing
+    the whole input again for every sample, while parts reuse a single parsed solution.
+*/
+pub fn bench(entry: &Entry, input: &str, settings: Settings) -> Result<Vec<(Phase, Stats)>, Error> {
+    let solution = (entry.parse)(input)?;
+    solution.part1()?;
+    solution.part2()?;
+    let phases: [(Phase, Vec<Duration>); 3] = [
+        (Phase::Parse, sample(settings, || (entry.parse)(input).is_ok())),
+        (Phase::Part1, sample(settings, || solution.part1().is_ok())),
+        (Phase::Part2, sample(settings, || solution.part2().is_ok())),
+    ];
+    Ok(phases
+        .into_iter()
+        .filter_map(|(phase, samples)| Some((phase, Stats::new(&samples)?)))
+        .collect())
+}
+
+/**
+    Machine readable record of a benchmark run, stored as TOML with one table per measured phase,
+    keyed by "<year>/<day>/<phase>", e.g. "2022/07/part1".
+*/
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Baseline {
+    #[serde(flatten)]
+    stats: BTreeMap<String, Stats>,
+}
+
+impl Baseline {
+    fn key(year: u16, day: u8, phase: Phase) -> String {
+        format!("{}/{:02}/{}", year, day, phase)
+    }
+
+    pub fn insert(&mut self, year: u16, day: u8, phase: Phase, stats: Stats) {
+        self.stats.insert(Self::key(year, day, phase), stats);
+    }
+
+    pub fn get(&self, year: u16, day: u8, phase: Phase) -> Option<&Stats> {
+        self.stats.get(&Self::key(year, day, phase))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, toml::to_string(self)?).map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+}
+
+/**
+    Relative change of the median compared to a baseline, as a percentage, when it is slower than
+    the baseline by more than threshold percent. Medians are compared rather than minimums, as they
+    are far less sensitive to a single lucky sample.
+*/
+pub fn regression(baseline: &Stats, current: &Stats, threshold: f64) -> Option<f64> {
+    let change = (current.median as f64 / baseline.median.max(1) as f64 - 1.0) * 100.0;
+    (change > threshold).then_some(change)
+}
+
+/// Human readable duration with a unit suited to its magnitude, e.g. 12.3µs
+pub fn format_nanos(nanos: u64) -> String {
+    match nanos {
+        0..=999 => format!("{}ns", nanos),
+        1_000..=999_999 => format!("{:.1}µs", nanos as f64 / 1e3),
+        1_000_000..=999_999_999 => format!("{:.1}ms", nanos as f64 / 1e6),
+        _ => format!("{:.2}s", nanos as f64 / 1e9),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn test_stats() {
+        let samples: Vec<Duration> = (1..=20).rev().map(Duration::from_nanos).collect();
+        assert_eq!(Stats::new(&samples), Some(Stats { min: 1, median: 10, p95: 19 }));
+        assert_eq!(Stats::new(&[Duration::from_nanos(5)]), Some(Stats { min: 5, median: 5, p95: 5 }));
+        assert_eq!(Stats::new(&[]), None);
+    }
+
+    #[test]
+    fn test_regression() {
+        let baseline = Stats { min: 90, median: 100, p95: 150 };
+        assert_eq!(regression(&baseline, &Stats { min: 90, median: 105, p95: 150 }, 10.0), None);
+        assert_eq!(regression(&baseline, &Stats { min: 90, median: 150, p95: 150 }, 10.0), Some(50.0));
+        assert_eq!(regression(&baseline, &Stats { min: 10, median: 50, p95: 60 }, 10.0), None);
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let mut baseline = Baseline::default();
+        baseline.insert(2022, 7, Phase::Part1, Stats { min: 1, median: 2, p95: 3 });
+        let text = toml::to_string(&baseline).unwrap();
+        assert!(text.contains("[\"2022/07/part1\"]"));
+        let loaded: Baseline = toml::from_str(&text).unwrap();
+        assert_eq!(loaded.get(2022, 7, Phase::Part1), Some(&Stats { min: 1, median: 2, p95: 3 }));
+        assert_eq!(loaded.get(2022, 7, Phase::Part2), None);
+    }
+
+    #[test]
+    fn test_bench() {
+        let entry = registry::find(2022, 6).unwrap();
+        let stats = bench(entry, "mjqjpqmgbljsphdztnvjfqwrcgsmlb", Settings { warm_up: 1, samples: 3 }).unwrap();
+        let phases: Vec<Phase> = stats.iter().map(|(phase, _)| *phase).collect();
+        assert_eq!(phases, vec![Phase::Parse, Phase::Part1, Phase::Part2]);
+        assert!(stats.iter().all(|(_, stats)| stats.min <= stats.median && stats.median <= stats.p95));
+    }
+
+    #[test]
+    fn test_format_nanos() {
+        assert_eq!(format_nanos(999), "999ns");
+        assert_eq!(format_nanos(12_345), "12.3µs");
+        assert_eq!(format_nanos(2_500_000), "2.5ms");
+        assert_eq!(format_nanos(3_000_000_000), "3.00s");
+    }
+}
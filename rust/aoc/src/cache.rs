@@ -0,0 +1,171 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solution::Error;
+
+/// An answer computed before, valid for the input and the solver version it was computed with
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct CachedAnswer {
+    pub part: u8,
+    /// SHA-256 of the input, as lowercase hexadecimal
+    pub input_sha256: String,
+    pub version: String,
+    pub answer: String,
+}
+
+/// Cached answers of a day, as stored in its file
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct DayFile {
+    #[serde(default)]
+    answers: Vec<CachedAnswer>,
+}
+
+/**
+    Answers of previous runs, stored in <dir>/<year>/dayNN.toml and keyed by part, input hash and
+    solver version. Storing an answer drops those of other versions, so that each file only keeps
+    the answers of the current solver, for every input it ran against.
+*/
+pub struct AnswerCache {
+    dir: PathBuf,
+    version: String,
+}
+
+impl AnswerCache {
+    pub fn new(dir: PathBuf, version: String) -> Self {
+        Self { dir, version }
+    }
+
+    /// Directory of the answer cache below the cache directory of the configuration
+    pub fn dir(cache_dir: &Path) -> PathBuf {
+        cache_dir.join("answers")
+    }
+
+    fn path(&self, year: u16, day: u8) -> PathBuf {
+        self.dir.join(year.to_string()).join(format!("day{:02}.toml", day))
+    }
+
+    fn load(&self, year: u16, day: u8) -> Result<DayFile, Error> {
+        let path = self.path(year, day);
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err).into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(DayFile::default()),
+            Err(err) => Err(format!("{}: {}", path.display(), err).into()),
+        }
+    }
+
+    /// Answer to a part computed from the very same input by the current solver, if any
+    pub fn get(&self, year: u16, day: u8, part: u8, input: &str) -> Result<Option<String>, Error> {
+        let input_sha256 = sha256(input);
+        Ok(self.load(year, day)?
+            .answers
+            .into_iter()
+            .find(|cached| cached.part == part && cached.input_sha256 == input_sha256 && cached.version == self.version)
+            .map(|cached| cached.answer))
+    }
+
+    /// Stores the answer to a part computed from input, replacing any answer of an older version
+    pub fn insert(&self, year: u16, day: u8, part: u8, input: &str, answer: &str) -> Result<(), Error> {
+        let input_sha256 = sha256(input);
+        let mut file = self.load(year, day)?;
+        file.answers.retain(|cached| cached.version == self.version && (cached.part, &cached.input_sha256) != (part, &input_sha256));
+        file.answers.push(CachedAnswer { part, input_sha256, version: self.version.clone(), answer: answer.to_string() });
+        let path = self.path(year, day);
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, toml::to_string(&file).expect("cached answers serialize to TOML")))
+            .map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    /// Removes the cached answers of a day, of a year, or every one of them. Returns the number of days cleared.
+    pub fn clear(&self, year: Option<u16>, day: Option<u8>) -> Result<usize, Error> {
+        let path = match (year, day) {
+            (Some(year), Some(day)) => self.path(year, day),
+            (Some(year), None) => self.dir.join(year.to_string()),
+            _ => self.dir.clone(),
+        };
+        let cleared = count_files(&path);
+        let removed = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        match removed {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(format!("{}: {}", path.display(), err).into()),
+            _ => Ok(cleared),
+        }
+    }
+}
+
+/// Files below path, or 1 if path is a file itself
+fn count_files(path: &Path) -> usize {
+    match fs::read_dir(path) {
+        Ok(entries) => entries.flatten().map(|entry| count_files(&entry.path())).sum(),
+        Err(_) => usize::from(path.is_file()),
+    }
+}
+
+/// Lowercase hexadecimal SHA-256 of text
+pub fn sha256(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/**
+    Version of the solvers compiled into this binary: the crate version followed by a hash of the
+    workspace sources, e.g. 0.1.0+3f2c1e0d9a8b7c6d, computed by build.rs.
+*/
+pub const VERSION: &str = env!("AOC_SOLVER_VERSION");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+
+    #[test]
+    fn test_version() {
+        let (crate_version, hash) = VERSION.split_once('+').unwrap();
+        assert_eq!(crate_version, env!("CARGO_PKG_VERSION"));
+        assert!(hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()), "{}", hash);
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(sha256("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_get_and_insert() {
+        let dir = temp_dir("cache-insert");
+        let cache = AnswerCache::new(dir.clone(), "1".to_string());
+        assert_eq!(cache.get(2022, 5, 1, "input").unwrap(), None);
+        cache.insert(2022, 5, 1, "input", "CMZ").unwrap();
+        cache.insert(2022, 5, 2, "input", "MCD").unwrap();
+        cache.insert(2022, 5, 1, "other input", "ABC").unwrap();
+        assert_eq!(cache.get(2022, 5, 1, "input").unwrap(), Some("CMZ".to_string()));
+        assert_eq!(cache.get(2022, 5, 1, "other input").unwrap(), Some("ABC".to_string()));
+        assert_eq!(cache.get(2022, 6, 1, "input").unwrap(), None);
+
+        let newer = AnswerCache::new(dir.clone(), "2".to_string());
+        assert_eq!(newer.get(2022, 5, 2, "input").unwrap(), None);
+        newer.insert(2022, 5, 2, "input", "multi\nline").unwrap();
+        assert_eq!(newer.get(2022, 5, 2, "input").unwrap(), Some("multi\nline".to_string()));
+        assert_eq!(cache.get(2022, 5, 1, "input").unwrap(), None);
+        assert_eq!(newer.load(2022, 5).unwrap().answers.len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_clear() {
+        let dir = temp_dir("cache-clear");
+        let cache = AnswerCache::new(dir.clone(), "1".to_string());
+        for (year, day) in [(2021, 1), (2022, 5), (2022, 7), (2022, 8)] {
+            cache.insert(year, day, 1, "input", "42").unwrap();
+        }
+        assert_eq!(cache.clear(Some(2022), Some(5)).unwrap(), 1);
+        assert_eq!(cache.clear(Some(2022), Some(5)).unwrap(), 0);
+        assert_eq!(cache.get(2022, 7, 1, "input").unwrap(), Some("42".to_string()));
+        assert_eq!(cache.clear(Some(2022), None).unwrap(), 2);
+        assert_eq!(cache.get(2021, 1, 1, "input").unwrap(), Some("42".to_string()));
+        assert_eq!(cache.clear(None, None).unwrap(), 1);
+        assert_eq!(cache.clear(None, None).unwrap(), 0);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use solution::Error;
+
+use crate::answers::Answers;
+use crate::registry;
+use crate::report::Format;
+
+/// Name of the configuration files, in the workspace directory and in the user's aoc directory
+pub const FILE_NAME: &str = "aoc.toml";
+
+/// An aoc.toml file as written, before its relative paths are resolved
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    year: Option<u16>,
+    format: Option<Format>,
+    session_file: Option<String>,
+    base_url: Option<String>,
+    answers: Option<String>,
+    cache: Option<String>,
+    cache_answers: Option<bool>,
+    #[serde(default)]
+    inputs: BTreeMap<String, String>,
+}
+
+/**
+    Settings read from aoc.toml files, where paths are relative to the file holding them and may
+    start with ~/. Paths of inputs and answers are templates, in which {year} and {day} are replaced
+    with the year and the two digits day, e.g.
+
+    ```toml
+    year = 2022
+    format = "text"
+    session_file = "~/.config/aoc/session"
+    answers = "{year}/answers.toml"
+    cache = "~/.cache/aoc"
+    cache_answers = true
+
+    [inputs]
+    default = "{year}/day{day}/resources/input/first_part.txt"
+    2021 = "~/inputs/2021/{day}.txt"
+    "2022/05" = "~/inputs/supply-stacks.txt"
+    ```
+
+    The input of a day comes from its "<year>/<day>" key, or else its year's, or else the default
+    one. Command line flags take precedence over all of these.
+*/
+#[derive(Default, Debug)]
+pub struct Config {
+    /// Year of the commands taking an optional year
+    pub year: Option<u16>,
+    /// Output format of run
+    pub format: Option<Format>,
+    /// File holding the session token, when AOC_SESSION isn't set
+    pub session_file: Option<PathBuf>,
+    /// Site to talk to, when neither --base-url nor AOC_BASE_URL are set
+    pub base_url: Option<String>,
+    answers: Option<String>,
+    /// Directory of files kept between runs, such as the request throttling stamp
+    pub cache: Option<PathBuf>,
+    /// Whether run reuses the answers cached below the cache directory, when neither --cache nor --no-cache are given
+    pub cache_answers: Option<bool>,
+    inputs: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Workspace configuration, next to the workspace manifest
+    pub fn workspace_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join(FILE_NAME)
+    }
+
+    /// User configuration, e.g. ~/.config/aoc/aoc.toml on Linux
+    pub fn user_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("aoc").join(FILE_NAME))
+    }
+
+    /// Reads the user configuration, then the workspace one, whose settings win. Both are optional.
+    pub fn load() -> Result<Self, Error> {
+        let user = match Self::user_path() {
+            Some(path) => Self::load_file(&path)?,
+            None => Self::default(),
+        };
+        Ok(user.merge(Self::load_file(&Self::workspace_path())?))
+    }
+
+    /// Reads a configuration file. A missing file simply holds no settings.
+    pub fn load_file(path: &Path) -> Result<Self, Error> {
+        let base = path.parent().unwrap_or(Path::new("."));
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_toml(&text, base).map_err(|err| format!("{}: {}", path.display(), err).into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(format!("{}: {}", path.display(), err).into()),
+        }
+    }
+
+    /// Parses a configuration whose relative paths are relative to base
+    pub fn from_toml(text: &str, base: &Path) -> Result<Self, Error> {
+        let file: ConfigFile = toml::from_str(text)?;
+        for key in file.inputs.keys() {
+            let valid = match key.split_once('/') {
+                Some((year, day)) => year.parse::<u16>().is_ok() && day.parse::<u8>().is_ok(),
+                None => key == "default" || key.parse::<u16>().is_ok(),
+            };
+            if !valid {
+                return Err(format!("invalid inputs key {}, expected default, <year> or <year>/<day>", key).into());
+            }
+        }
+        let resolve = |path: String| resolve(base, &path).to_string_lossy().into_owned();
+        Ok(Self {
+            year: file.year,
+            format: file.format,
+            session_file: file.session_file.map(|path| resolve(path).into()),
+            base_url: file.base_url,
+            answers: file.answers.map(resolve),
+            cache: file.cache.map(|path| resolve(path).into()),
+            cache_answers: file.cache_answers,
+            inputs: file.inputs.into_iter().map(|(key, path)| (key, resolve(path))).collect(),
+        })
+    }
+
+    /// Settings of self overridden by those of other
+    fn merge(mut self, other: Self) -> Self {
+        self.inputs.extend(other.inputs);
+        Self {
+            year: other.year.or(self.year),
+            format: other.format.or(self.format),
+            session_file: other.session_file.or(self.session_file),
+            base_url: other.base_url.or(self.base_url),
+            answers: other.answers.or(self.answers),
+            cache: other.cache.or(self.cache),
+            cache_answers: other.cache_answers.or(self.cache_answers),
+            inputs: self.inputs,
+        }
+    }
+
+    /// Input file of a day, by default its resources/input/first_part.txt
+    pub fn input(&self, year: u16, day: u8) -> PathBuf {
+        let day_key = self.inputs.iter().find(|(key, _)| {
+            key.split_once('/').is_some_and(|(key_year, key_day)| key_year.parse() == Ok(year) && key_day.parse() == Ok(day))
+        });
+        let template = day_key
+            .map(|(_, template)| template)
+            .or_else(|| self.inputs.get(&year.to_string()))
+            .or_else(|| self.inputs.get("default"));
+        match template {
+            Some(template) => PathBuf::from(fill(template, year, day)),
+            None => registry::input_path(year, day),
+        }
+    }
+
+    /// Expected answers of a year, by default <year>/answers.toml
+    pub fn answers(&self, year: u16) -> PathBuf {
+        match &self.answers {
+            Some(template) => PathBuf::from(fill(template, year, 0)),
+            None => Answers::path(year),
+        }
+    }
+
+    /**
+        Cache directory, by default aoc below the user's cache directory, e.g. ~/.cache/aoc or
+        $XDG_CACHE_HOME/aoc on Linux. The temporary directory is only a last resort for systems
+        without one.
+    */
+    pub fn cache_dir(&self) -> PathBuf {
+        self.cache.clone()
+            .or_else(|| dirs::cache_dir().map(|dir| dir.join("aoc")))
+            .unwrap_or_else(|| env::temp_dir().join("aoc"))
+    }
+}
+
+fn fill(template: &str, year: u16, day: u8) -> String {
+    template.replace("{year}", &year.to_string()).replace("{day}", &format!("{:02}", day))
+}
+
+/// path relative to base, with a leading ~/ standing for the home directory
+fn resolve(base: &Path, path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => base.join(path),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve(Path::new("/workspace"), "inputs/1.txt"), PathBuf::from("/workspace/inputs/1.txt"));
+        assert_eq!(resolve(Path::new("/workspace"), "/inputs/1.txt"), PathBuf::from("/inputs/1.txt"));
+        assert_eq!(resolve(Path::new("/workspace"), "~/inputs/1.txt"), dirs::home_dir().unwrap().join("inputs/1.txt"));
+    }
+
+    #[test]
+    fn test_input() {
+        let config = Config::from_toml("[inputs]\n\
+            default = \"inputs/{year}/{day}.txt\"\n\
+            2021 = \"/old/{day}/input\"\n\
+            \"2022/5\" = \"/stacks.txt\"\n", Path::new("/workspace")).unwrap();
+        assert_eq!(config.input(2022, 5), PathBuf::from("/stacks.txt"));
+        assert_eq!(config.input(2022, 6), PathBuf::from("/workspace/inputs/2022/06.txt"));
+        assert_eq!(config.input(2021, 6), PathBuf::from("/old/06/input"));
+        assert_eq!(Config::default().input(2022, 6), registry::input_path(2022, 6));
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml("year = 2021\nformat = \"ndjson\"\nanswers = \"answers/{year}.toml\"\ncache = \"cache\"\ncache_answers = true\n",
+                                       Path::new("/workspace")).unwrap();
+        assert_eq!(config.year, Some(2021));
+        assert_eq!(config.format, Some(Format::Ndjson));
+        assert_eq!(config.answers(2021), PathBuf::from("/workspace/answers/2021.toml"));
+        assert_eq!(config.cache_dir(), PathBuf::from("/workspace/cache"));
+        assert!(Config::default().cache_dir().ends_with("aoc"));
+        assert_eq!(config.cache_answers, Some(true));
+        assert!(Config::from_toml("colour = \"red\"\n", Path::new(".")).is_err());
+        assert!(Config::from_toml("format = \"xml\"\n", Path::new(".")).is_err());
+        assert!(Config::from_toml("[inputs]\nlatest = \"x\"\n", Path::new(".")).unwrap_err().to_string().contains("invalid inputs key"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let dir = temp_dir("config");
+        fs::create_dir_all(dir.join("user")).unwrap();
+        fs::write(dir.join("user/aoc.toml"), "year = 2021\nbase_url = \"http://user\"\n[inputs]\ndefault = \"{day}.txt\"\n2022 = \"user/{day}\"\n").unwrap();
+        fs::write(dir.join("aoc.toml"), "year = 2022\n[inputs]\n2022 = \"workspace/{day}\"\n").unwrap();
+        let user = Config::load_file(&dir.join("user/aoc.toml")).unwrap();
+        let config = user.merge(Config::load_file(&dir.join("aoc.toml")).unwrap());
+        assert_eq!(config.year, Some(2022));
+        assert_eq!(config.base_url.as_deref(), Some("http://user"));
+        assert_eq!(config.input(2022, 1), dir.join("workspace/01"));
+        assert_eq!(config.input(2020, 1), dir.join("user/01.txt"));
+        assert!(Config::load_file(&dir.join("missing.toml")).unwrap().inputs.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
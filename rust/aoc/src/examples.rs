@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+
+use solution::Error;
+
+use crate::html;
+use crate::registry::Entry;
+
+/// Example input of a part and its expected answer, as found in a saved puzzle page
+#[derive(PartialEq, Debug)]
+pub struct Example {
+    pub part: u8,
+    pub input: String,
+    pub answer: String,
+    /// Whether the day's solution gives answer on input, which confirms the pairing
+    pub confirmed: bool,
+}
+
+/**
+    Pairs the example blocks of a puzzle page with the answer of each part. The page holds one
+    <article> per part, whose last emphasized code, e.g. <code><em>13140</em></code>, is the answer
+    for the example. As parts often show several blocks, such as intermediate states, the example is
+    the first <pre><code> block of the page for which the day's solution gives that answer. Without
+    such a block, it falls back to the first block of the part's article, or else of the page.
+*/
+pub fn extract(html: &str, entry: Option<&Entry>) -> Vec<Example> {
+    let blocks: Vec<String> = html::elements(html, "pre")
+        .into_iter()
+        .filter(|block| block.trim_start().starts_with("<code>"))
+        .map(html::text)
+        .collect();
+    let mut articles = html::elements(html, "article");
+    if articles.is_empty() {
+        articles.push(html);
+    }
+    articles
+        .into_iter()
+        .zip(1..=2)
+        .filter_map(|(article, part)| {
+            let answer = emphasized_code(article)?;
+            let confirmed = blocks.iter().find(|block| entry.is_some_and(|entry| solves(entry, block, part, &answer)));
+            let input = confirmed
+                .or_else(|| html::elements(article, "pre").first().and_then(|first| {
+                    let first = html::text(first);
+                    blocks.iter().find(|block| **block == first)
+                }))
+                .or(blocks.first())?;
+            Some(Example { part, input: input.clone(), answer, confirmed: confirmed.is_some() })
+        })
+        .collect()
+}
+
+/// Text of the last <code><em> or <em><code> element of an article
+fn emphasized_code(article: &str) -> Option<String> {
+    let (start, open) = ["<code><em>", "<em><code>"]
+        .into_iter()
+        .filter_map(|open| Some((article.rfind(open)?, open)))
+        .max()?;
+    let content = &article[start + open.len()..];
+    Some(html::text(&content[..content.find('<')?]))
+}
+
+fn solves(entry: &Entry, input: &str, part: u8, answer: &str) -> bool {
+    let Ok(solution) = (entry.parse)(input) else { return false };
+    let actual = if part == 1 { solution.part1() } else { solution.part2() };
+    actual.is_ok_and(|actual| actual.to_string().trim_end() == answer.trim_end())
+}
+
+/**
+    Writes each example into the resources/test directory of the day in dir, as partN.txt holding
+    the input and partN.answer holding the answer, then generates tests/examples.rs checking them
+    against the day's model, e.g. "aoc_2022_day10::CathodeRayTube". Previous files are overwritten.
+*/
+pub fn write(dir: &Path, type_name: &str, examples: &[Example]) -> Result<(), Error> {
+    let resources = dir.join("resources/test");
+    fs::create_dir_all(&resources)?;
+    fs::create_dir_all(dir.join("tests"))?;
+    let (crate_name, name) = type_name.split_once("::").ok_or_else(|| format!("{} isn't a path to a model", type_name))?;
+    let mut tests = format!(
+        "//! Generated by aoc examples from the saved puzzle page, run it again rather than editing this file\n\n\
+         use {}::{};\n\
+         use solution::Solution;\n",
+        crate_name, name
+    );
+    for example in examples {
+        fs::write(resources.join(format!("part{}.txt", example.part)), &example.input)?;
+        fs::write(resources.join(format!("part{}.answer", example.part)), format!("{}\n", example.answer.trim_end()))?;
+        tests.push_str(&format!(
+            "\n#[test]\n\
+             fn test_part{part}_example() {{\n    \
+                 let solution = {name}::parse(include_str!(\"../resources/test/part{part}.txt\")).unwrap();\n    \
+                 let expected = include_str!(\"../resources/test/part{part}.answer\");\n    \
+                 assert_eq!(solution.part{part}().unwrap().to_string().trim_end(), expected.trim_end());\n\
+             }}\n",
+            part = example.part,
+            name = name
+        ));
+    }
+    fs::write(dir.join("tests/examples.rs"), tests)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+    use crate::registry;
+
+    const CALORIES: &str = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+
+    fn page() -> String {
+        format!("<main>\n\
+            <article class=\"day-desc\"><h2>--- Day 1: Calorie Counting ---</h2><p>For example:</p>\
+            <pre><code>{}</code></pre><p>This list represents <em>5</em> Elves.</p>\
+            <pre><code>4000\n</code></pre><p>The Elf carrying the most is carrying <em><code>24000</code></em> Calories.</p>\
+            <p>Find the Elf carrying the most Calories.</p></article>\n\
+            <p>Your puzzle answer was <code>74198</code>.</p>\n\
+            <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>\
+            <p>In the example above, the top three Elves total <code><em>45000</em></code>.</p></article>\n</main>", CALORIES)
+    }
+
+    #[test]
+    fn test_extract_confirmed() {
+        let examples = extract(&page(), registry::find(2022, 1));
+        assert_eq!(examples, vec![
+            Example { part: 1, input: CALORIES.to_string(), answer: "24000".to_string(), confirmed: true },
+            Example { part: 2, input: CALORIES.to_string(), answer: "45000".to_string(), confirmed: true },
+        ]);
+    }
+
+    #[test]
+    fn test_extract_unconfirmed() {
+        // Day 2 can't parse calories, so part 1 falls back to its article and part 2 to the page
+        let examples = extract(&page(), registry::find(2022, 2));
+        assert_eq!(examples.iter().map(|example| (example.part, example.confirmed)).collect::<Vec<_>>(), vec![(1, false), (2, false)]);
+        assert!(examples.iter().all(|example| example.input == CALORIES));
+        assert_eq!(extract("<p>No example</p>", None), vec![]);
+    }
+
+    #[test]
+    fn test_emphasized_code() {
+        assert_eq!(emphasized_code("<code><em>1</em></code> then <em><code>&lt;2&gt;</code></em>").as_deref(), Some("<2>"));
+        assert_eq!(emphasized_code("<em>1</em> <code>2</code>"), None);
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = temp_dir("examples");
+        write(&dir, "aoc_2022_day01::CalorieCounting", &extract(&page(), registry::find(2022, 1))).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("resources/test/part1.txt")).unwrap(), CALORIES);
+        assert_eq!(fs::read_to_string(dir.join("resources/test/part2.answer")).unwrap(), "45000\n");
+        let tests = fs::read_to_string(dir.join("tests/examples.rs")).unwrap();
+        assert!(tests.contains("use aoc_2022_day01::CalorieCounting;"));
+        assert!(tests.contains("fn test_part2_example() {"));
+        assert!(write(&dir, "CalorieCounting", &[]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
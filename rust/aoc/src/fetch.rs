@@ -0,0 +1,234 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use solution::Error;
+
+/// Site serving the puzzles, overridable so that tests can use a local stand-in server
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Sent with every request so that the site's maintainers can tell where the traffic comes from
+pub const USER_AGENT: &str = concat!(
+    "AdventOfCode-rust-aoc/", env!("CARGO_PKG_VERSION"), " (+", env!("CARGO_PKG_REPOSITORY"), "; aoc fetch via ureq)"
+);
+
+/// Environment variables overriding the session token and the base URL
+pub const SESSION_VARIABLE: &str = "AOC_SESSION";
+pub const BASE_URL_VARIABLE: &str = "AOC_BASE_URL";
+
+/// File holding the session token when AOC_SESSION isn't set, e.g. ~/.config/aoc/session on Linux
+pub fn session_file() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("aoc").join("session"))
+}
+
+/// Session cookie read from AOC_SESSION, or else from file, by default the session file
+pub fn session_token(file: Option<&Path>) -> Option<String> {
+    let token = match env::var(SESSION_VARIABLE) {
+        Ok(token) => token,
+        Err(_) => fs::read_to_string(file.map(Path::to_path_buf).or_else(session_file)?).ok()?,
+    };
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/**
+    Keeps requests at least interval apart, even across invocations, by sleeping until interval has
+    elapsed since the last modification of a stamp file.
+*/
+pub struct Throttle {
+    stamp: PathBuf,
+    interval: Duration,
+}
+
+impl Throttle {
+    pub fn new(stamp: PathBuf, interval: Duration) -> Self {
+        Self { stamp, interval }
+    }
+
+    /// One request every 5 seconds at most, tracked in a stamp file of the cache directory
+    pub fn in_cache(cache: &Path) -> Self {
+        Self::new(cache.join("aoc-last-request"), Duration::from_secs(5))
+    }
+
+    fn wait(&self) -> io::Result<()> {
+        let last_request = fs::metadata(&self.stamp).and_then(|metadata| metadata.modified());
+        if let Ok(elapsed) = last_request.map(|time| SystemTime::now().duration_since(time).unwrap_or_default()) {
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+        if let Some(dir) = self.stamp.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.stamp, b"")
+    }
+}
+
+/// Whether fetch() downloaded the input or found it already cached
+#[derive(PartialEq, Eq, Debug)]
+pub enum Fetched {
+    Cached,
+    Downloaded,
+}
+
+/// Talks to the puzzles site, authenticated with the session cookie and throttled
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: Option<String>,
+    /// Where the session token is expected to be written, named when it's missing
+    session_file: Option<PathBuf>,
+    throttle: Throttle,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: Option<String>, throttle: Throttle) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build();
+        Self { agent, base_url: base_url.trim_end_matches('/').to_string(), session, session_file: session_file(), throttle }
+    }
+
+    /// Names file rather than the default session file when there's no session token
+    pub fn with_session_file(self, file: Option<PathBuf>) -> Self {
+        Self { session_file: file.or(self.session_file), ..self }
+    }
+
+    /**
+        Downloads the input of a day into cache, unless cache already holds a non empty file. The
+        file is written through a temporary sibling so that an interrupted download never looks
+        like a cached input.
+    */
+    pub fn fetch(&self, year: u16, day: u8, cache: &Path) -> Result<Fetched, Error> {
+        if fs::metadata(cache).is_ok_and(|metadata| metadata.len() > 0) {
+            return Ok(Fetched::Cached);
+        }
+        let text = self.send(&format!("{}/day/{}/input", year, day), None)?;
+        if let Some(parent) = cache.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = cache.with_extension("part");
+        fs::write(&partial, text)?;
+        fs::rename(&partial, cache)?;
+        Ok(Fetched::Downloaded)
+    }
+
+    /**
+        GETs, or POSTs form when present, the page at path below the base URL and returns its body.
+        Fails without any request if there is no session token.
+    */
+    pub(crate) fn send(&self, path: &str, form: Option<&[(&str, &str)]>) -> Result<String, Error> {
+        let session = self.session.as_ref().ok_or_else(|| match &self.session_file {
+            Some(file) => format!("no session token, set {} or write it to {}", SESSION_VARIABLE, file.display()),
+            None => format!("no session token, set {}", SESSION_VARIABLE),
+        })?;
+        let url = format!("{}/{}", self.base_url, path);
+        self.throttle.wait()?;
+        let cookie = format!("session={}", session);
+        let response = match form {
+            Some(form) => self.agent.post(&url).set("Cookie", &cookie).send_form(form),
+            None => self.agent.get(&url).set("Cookie", &cookie).call(),
+        };
+        match response {
+            Ok(response) => Ok(response.into_string()?),
+            Err(ureq::Error::Status(400, _)) => Err(format!("{}: session token rejected", url).into()),
+            Err(ureq::Error::Status(404, _)) => Err(format!("{}: puzzle not found or not unlocked yet", url).into()),
+            Err(err) => Err(format!("{}: {}", url, err).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::process;
+    use std::sync::mpsc;
+
+    /// Serves a single request with the given status and body, sending back the request's lines
+    pub(crate) fn serve_once(status: &str, body: &'static str) -> (String, mpsc::Receiver<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let status = status.to_string();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request: Vec<String> = reader.by_ref()
+                .lines()
+                .map(Result::unwrap)
+                .take_while(|line| !line.is_empty())
+                .collect();
+            let content_length = request.iter()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length: ")?.parse().ok())
+                .unwrap_or(0);
+            let mut content = vec![0; content_length];
+            reader.read_exact(&mut content).unwrap();
+            request.push(String::from_utf8(content).unwrap());
+            write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+            sender.send(request).unwrap();
+        });
+        (base_url, receiver)
+    }
+
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_fetch_downloads_then_caches() {
+        let dir = temp_dir("fetch");
+        let (base_url, requests) = serve_once("200 OK", "1000\n2000\n");
+        let throttle = Throttle::new(dir.join("stamp"), Duration::ZERO);
+        let client = Client::new(&format!("{}/", base_url), Some("abc".to_string()), throttle);
+        let cache = dir.join("2022/day01/first_part.txt");
+
+        assert_eq!(client.fetch(2022, 1, &cache).unwrap(), Fetched::Downloaded);
+        assert_eq!(fs::read_to_string(&cache).unwrap(), "1000\n2000\n");
+        let request = requests.recv().unwrap();
+        assert_eq!(request[0], "GET /2022/day/1/input HTTP/1.1");
+        assert!(request.iter().any(|line| line.eq_ignore_ascii_case(&format!("user-agent: {}", USER_AGENT))));
+        assert!(USER_AGENT.contains("(+https://github.com/dhulke/AdventOfCode; "), "{}", USER_AGENT);
+        assert!(request.iter().any(|line| line.eq_ignore_ascii_case("cookie: session=abc")));
+
+        // The stand-in server is gone, so this only succeeds without a request
+        assert_eq!(client.fetch(2022, 1, &cache).unwrap(), Fetched::Cached);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let dir = temp_dir("fetch-errors");
+        let cache = dir.join("first_part.txt");
+        let (base_url, _requests) = serve_once("400 Bad Request", "");
+        let client = Client::new(&base_url, Some("expired".to_string()), Throttle::new(dir.join("stamp"), Duration::ZERO));
+        assert!(client.fetch(2022, 1, &cache).unwrap_err().to_string().contains("session token rejected"));
+        assert!(!cache.exists());
+
+        let client = Client::new(&base_url, None, Throttle::new(dir.join("stamp"), Duration::ZERO));
+        assert!(client.fetch(2022, 1, &cache).unwrap_err().to_string().starts_with("no session token"));
+        let client = client.with_session_file(Some(dir.join("token")));
+        assert_eq!(client.fetch(2022, 1, &cache).unwrap_err().to_string(),
+                   format!("no session token, set AOC_SESSION or write it to {}", dir.join("token").display()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_throttle() {
+        let dir = temp_dir("throttle");
+        let throttle = Throttle::new(dir.join("stamp"), Duration::from_millis(200));
+        throttle.wait().unwrap();
+        let start = SystemTime::now();
+        throttle.wait().unwrap();
+        assert!(start.elapsed().unwrap() >= Duration::from_millis(150));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+/**
+    Inner HTML of every <tag> element in html, in document order. This is only meant for the
+    puzzle pages of the site, so nested elements of the same tag aren't supported.
+*/
+pub fn elements<'a>(html: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}", tag), format!("</{}>", tag));
+    let mut elements = vec![];
+    let mut rest = html;
+    while let Some(start) = rest.find(&open) {
+        let after_name = &rest[start + open.len()..];
+        // Skips longer tag names sharing the same prefix, e.g. <pre> when looking for <p>
+        if !after_name.starts_with(['>', ' ', '\t', '\n', '/']) {
+            rest = after_name;
+            continue;
+        }
+        let Some(content_start) = after_name.find('>').map(|end| end + 1) else { break };
+        let Some(content_end) = after_name[content_start..].find(&close) else { break };
+        elements.push(&after_name[content_start..content_start + content_end]);
+        rest = &after_name[content_start + content_end + close.len()..];
+    }
+    elements
+}
+
+/// Text of an HTML fragment, with its tags removed and its character references decoded
+pub fn text(fragment: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in fragment.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    unescape(&text)
+}
+
+/// Decodes named references used by the site, such as &lt;, and numeric ones, such as &#39;
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let decoded = match reference {
+            Some("lt") => Some('<'),
+            Some("gt") => Some('>'),
+            Some("amp") => Some('&'),
+            Some("quot") => Some('"'),
+            Some("apos") => Some('\''),
+            Some("nbsp") => Some('\u{a0}'),
+            Some(reference) if reference.starts_with("#x") => u32::from_str_radix(&reference[2..], 16).ok().and_then(char::from_u32),
+            Some(reference) if reference.starts_with('#') => reference[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match (decoded, reference) {
+            (Some(c), Some(reference)) => {
+                unescaped.push(c);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_elements() {
+        let html = "<main><p>One</p><pre><code>1\n2\n</code></pre><p class=\"day-success\">Two <em>2</em></p></main>";
+        assert_eq!(elements(html, "p"), vec!["One", "Two <em>2</em>"]);
+        assert_eq!(elements(html, "code"), vec!["1\n2\n"]);
+        assert_eq!(elements(html, "article"), Vec::<&str>::new());
+        assert_eq!(elements("<p>unclosed", "p"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(text("<em>$ cd /</em> &amp;&amp; ls &lt;dir&gt;"), "$ cd / && ls <dir>");
+        assert_eq!(text("it&#39;s &#x41; &quot;test&quot; &unknown; &"), "it's A \"test\" &unknown; &");
+    }
+}
@@ -0,0 +1,15 @@
+pub mod answers;
+pub mod batch;
+pub mod bench;
+pub mod cache;
+pub mod config;
+pub mod examples;
+pub mod fetch;
+pub mod html;
+pub mod registry;
+pub mod repl;
+pub mod report;
+pub mod scaffold;
+pub mod submit;
+pub mod trace;
+pub mod watch;
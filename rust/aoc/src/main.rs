@@ -0,0 +1,765 @@
+use clap::{Parser, Subcommand};
+use std::collections::{btree_map, BTreeMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use aoc::answers::{self, Answers, Verdict};
+use aoc::batch;
+use aoc::bench::{self, Baseline, Settings};
+use aoc::cache::{self, AnswerCache};
+use aoc::config::Config;
+use aoc::examples;
+use aoc::fetch::{self, Client, Fetched, Throttle};
+use aoc::registry::{self, Entry};
+use aoc::repl;
+use aoc::report::{self, Format, Order, Record};
+use aoc::scaffold::{self, Puzzle};
+use aoc::submit::{Outcome, Submissions};
+use aoc::trace;
+use aoc::watch::{self, Snapshot};
+
+#[derive(Parser)]
+struct Arguments {
+    #[command(subcommand)]
+    command: Command,
+    /// Prints the days' debug events, such as instructions and moves, and with -vv their trace events
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Tracing directives overriding the verbosity per day or module, e.g. aoc_2022_day09=trace
+    #[arg(long, global = true)]
+    trace_filter: Option<String>,
+    /// Writes the enabled events to this file as JSON lines instead of printing them to stderr
+    #[arg(long, global = true)]
+    trace_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs both parts of a day, of every day of a year, or of every day of every year with --all
+    Run {
+        /// Puzzle year, e.g. 2022
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        year: Option<u16>,
+        /// Puzzle day, from 1 to 25. Runs every day of the year when missing
+        day: Option<u8>,
+        /// Input file name, or - for the standard input. Defaults to aoc.toml's, or else the day's resources/input/first_part.txt
+        #[arg(short, long, requires = "day")]
+        input: Option<PathBuf>,
+        /// Runs every known day of every year against its default input
+        #[arg(short, long)]
+        all: bool,
+        /// Output format, json and ndjson print one record per part. Defaults to aoc.toml's, or else text
+        #[arg(short, long, value_enum)]
+        format: Option<Format>,
+        /// Parts run concurrently when running several days. Defaults to the available parallelism
+        #[arg(short, long, conflicts_with = "day", value_parser = clap::value_parser!(u64).range(1..))]
+        jobs: Option<u64>,
+        /// Order of the summary table printed when running several days
+        #[arg(long, conflicts_with = "day", value_enum, default_value_t)]
+        sort: Order,
+        /// Reuses the answers cached for the same inputs and solvers, and caches new ones. Defaults to aoc.toml's cache_answers
+        #[arg(long, conflicts_with = "no_cache")]
+        cache: bool,
+        /// Solves every part even if aoc.toml enables the answer cache
+        #[arg(long)]
+        no_cache: bool,
+    },
+    /// Runs a day against every input file of a directory, checking each against its *.answers sidecar if any
+    Batch {
+        /// Puzzle year, e.g. 2022
+        year: u16,
+        /// Puzzle day, from 1 to 25
+        day: u8,
+        /// Directory of the inputs, e.g. the team members' ones, with optional sidecars such as alice.txt.answers next to alice.txt
+        dir: PathBuf,
+        /// Inputs run concurrently. Defaults to the available parallelism
+        #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+        jobs: Option<u64>,
+    },
+    /// Removes the cached answers of every day, or only those of a year or a single day
+    ClearCache {
+        /// Only clears this year
+        year: Option<u16>,
+        /// Only clears this day
+        #[arg(requires = "year")]
+        day: Option<u8>,
+    },
+    /// Checks every day, or only those of a year or a single day, against <year>/answers.toml
+    Verify {
+        /// Only verifies this year
+        year: Option<u16>,
+        /// Only verifies this day
+        #[arg(requires = "year")]
+        day: Option<u8>,
+    },
+    /// Times parsing and each part of every day, or only those of a year or a single day
+    Bench {
+        /// Only benchmarks this year
+        year: Option<u16>,
+        /// Only benchmarks this day
+        #[arg(requires = "year")]
+        day: Option<u8>,
+        /// Untimed runs of each phase before sampling
+        #[arg(long, default_value_t = 3)]
+        warm_up: usize,
+        /// Timed runs of each phase
+        #[arg(short, long, default_value_t = 20, value_parser = clap::value_parser!(u64).range(1..))]
+        samples: u64,
+        /// Writes the results to this baseline file
+        #[arg(long)]
+        save: Option<PathBuf>,
+        /// Compares the results with this baseline file and fails on regressions
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Slowdown of the median, in percent, above which a phase is flagged as a regression
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+    },
+    /// Downloads a day's personal input into its resources/input/first_part.txt, unless already there
+    Fetch {
+        /// Puzzle year, e.g. 2022
+        year: u16,
+        /// Puzzle day, from 1 to 25
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Site to download from. Defaults to AOC_BASE_URL, or else aoc.toml's, or else https://adventofcode.com
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Prints a random valid input for a day, e.g. for stress testing
+    Gen {
+        /// Puzzle day, from 1 to 25
+        day: u8,
+        /// Puzzle year. Defaults to aoc.toml's, or else the latest implemented year
+        #[arg(short, long)]
+        year: Option<u16>,
+        /// Seed of the random number generator. Defaults to a random seed, printed to stderr
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Scale of the input, such as its number of lines or groups, depending on the day
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+        size: u64,
+    },
+    /// Submits the answer to a part, unless it's already known to be wrong, and records the outcome
+    Submit {
+        /// Puzzle year, e.g. 2022
+        year: u16,
+        /// Puzzle day, from 1 to 25
+        day: u8,
+        /// Puzzle part, 1 or 2
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+        /// Answer to submit instead of the one computed from the day's personal input
+        #[arg(short, long)]
+        answer: Option<String>,
+        /// Site to submit to. Defaults to AOC_BASE_URL, or else aoc.toml's, or else https://adventofcode.com
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Creates the crate of a new day from a template and registers it in the workspace and the runner
+    New {
+        /// Puzzle year, e.g. 2022
+        year: u16,
+        /// Puzzle day, from 1 to 25
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Locally saved puzzle page, providing the day's title and its example as a test input
+        #[arg(long)]
+        html: Option<PathBuf>,
+    },
+    /// Re-runs a day's tests and both parts whenever its src or resources directory changes
+    Watch {
+        /// Puzzle day, from 1 to 25
+        day: u8,
+        /// Puzzle year. Defaults to aoc.toml's, or else the latest implemented year
+        #[arg(short, long)]
+        year: Option<u16>,
+        /// Milliseconds between two checks for changes
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+    },
+    /// Runs the tests of every day, or only those of a year or a single day, including their snapshot tests
+    Test {
+        /// Only tests this year
+        year: Option<u16>,
+        /// Only tests this day
+        #[arg(requires = "year")]
+        day: Option<u8>,
+        /// Writes the renderings of snapshot tests to their resources/snapshots files instead of comparing them
+        #[arg(long)]
+        update_snapshots: bool,
+    },
+    /// Loads a day's input once and explores its parsed model interactively, e.g. stepping through moves
+    Repl {
+        /// Puzzle day, from 1 to 25
+        day: u8,
+        /// Puzzle year. Defaults to aoc.toml's, or else the latest implemented year
+        #[arg(short, long)]
+        year: Option<u16>,
+        /// Input file name. Defaults to aoc.toml's, or else the day's resources/input/first_part.txt
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Extracts the examples and their answers from a saved puzzle page into the day's tests
+    Examples {
+        /// Puzzle year, e.g. 2022
+        year: u16,
+        /// Puzzle day, from 1 to 25
+        day: u8,
+        /// Locally saved puzzle page, holding both parts once the first one is solved
+        #[arg(long)]
+        html: PathBuf,
+    },
+}
+
+fn main() {
+    let args = Arguments::parse();
+    let directives = trace::directives(args.verbose, args.trace_filter.as_deref());
+    if let Err(err) = trace::init(&directives, args.trace_file.as_deref()) {
+        eprintln!("Error setting up tracing: {}", err);
+        process::exit(1);
+    }
+    let config = Config::load().unwrap_or_else(|err| {
+        eprintln!("Error reading configuration: {}", err);
+        process::exit(1);
+    });
+
+    match args.command {
+        Command::Run { year: Some(year), day: Some(day), input, format, cache, no_cache, .. } => {
+            let entry = match registry::find(year, day) {
+                Some(entry) => entry,
+                None => {
+                    eprintln!("Day {} of {} isn't implemented", day, year);
+                    process::exit(1);
+                }
+            };
+            let file_name = input.unwrap_or_else(|| config.input(year, day));
+            let cache = answer_cache(&config, cache, no_cache);
+            if !run(&[(entry, file_name)], format.or(config.format).unwrap_or_default(), cache.as_ref()) {
+                process::exit(1);
+            }
+        }
+        Command::Run { year, format, jobs, sort, cache, no_cache, .. } => {
+            let days: Vec<(&Entry, PathBuf)> = select(year, None)
+                .into_iter()
+                .map(|entry| (entry, config.input(entry.year, entry.day)))
+                .collect();
+            let jobs = jobs.map_or_else(|| thread::available_parallelism().map_or(1, usize::from), |jobs| jobs as usize);
+            let cache = answer_cache(&config, cache, no_cache);
+            if !run_all(&days, jobs, format.or(config.format).unwrap_or_default(), sort, cache.as_ref()) {
+                process::exit(1);
+            }
+        }
+        Command::Batch { year, day, dir, jobs } => {
+            let Some(entry) = registry::find(year, day) else {
+                eprintln!("Day {} of {} isn't implemented", day, year);
+                process::exit(1);
+            };
+            let inputs = match batch::inputs(&dir) {
+                Ok(inputs) if inputs.is_empty() => {
+                    eprintln!("No input file in {}", dir.display());
+                    process::exit(1);
+                }
+                Ok(inputs) => inputs,
+                Err(err) => {
+                    eprintln!("Error listing inputs: {}", err);
+                    process::exit(1);
+                }
+            };
+            let jobs = jobs.map_or_else(|| thread::available_parallelism().map_or(1, usize::from), |jobs| jobs as usize);
+            let rows = batch::run(entry, &inputs, jobs);
+            print!("{}", batch::table(&rows));
+            if rows.iter().any(|row| row.verdict.is_failure()) {
+                process::exit(1);
+            }
+        }
+        Command::ClearCache { year, day } => {
+            // The version doesn't matter when removing files
+            let cache = AnswerCache::new(AnswerCache::dir(&config.cache_dir()), String::new());
+            match cache.clear(year, day) {
+                Ok(cleared) => println!("Cleared the cached answers of {} day(s)", cleared),
+                Err(err) => {
+                    eprintln!("Error clearing cache: {}", err);
+                    process::exit(1);
+                }
+            }
+        }
+        Command::Verify { year, day } => {
+            if !verify(&select(year, day), &config) {
+                process::exit(1);
+            }
+        }
+        Command::Bench { year, day, warm_up, samples, save, baseline, threshold } => {
+            let baseline = baseline.map(|path| Baseline::load(&path)).transpose().unwrap_or_else(|err| {
+                eprintln!("Error reading baseline: {}", err);
+                process::exit(1);
+            });
+            let settings = Settings { warm_up, samples: samples as usize };
+            if !benchmark(&select(year, day), &config, settings, baseline.as_ref(), threshold, save.as_deref()) {
+                process::exit(1);
+            }
+        }
+        Command::Fetch { year, day, base_url } => {
+            let client = client(base_url, &config);
+            let cache = config.input(year, day);
+            match client.fetch(year, day, &cache) {
+                Ok(Fetched::Cached) => println!("Already cached in {}", cache.display()),
+                Ok(Fetched::Downloaded) => println!("Downloaded to {}", cache.display()),
+                Err(err) => {
+                    eprintln!("Error fetching input: {}", err);
+                    process::exit(1);
+                }
+            }
+        }
+        Command::Gen { day, year, seed, size } => {
+            let year = year.or(config.year).unwrap_or_else(|| registry::solutions().last().map_or(0, |entry| entry.year));
+            let Some(entry) = registry::find(year, day) else {
+                eprintln!("Day {} of {} isn't implemented", day, year);
+                process::exit(1);
+            };
+            let seed = seed.unwrap_or_else(|| {
+                let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64);
+                eprintln!("Seed: {}", seed);
+                seed
+            });
+            print!("{}", (entry.generate)(&mut solution::random(seed), size as usize));
+        }
+        Command::Submit { year, day, part, answer, base_url } => {
+            let answer = answer.unwrap_or_else(|| solve(&config, year, day, part));
+            if !submit(&client(base_url, &config), &config.cache_dir(), year, day, part, &answer) {
+                process::exit(1);
+            }
+        }
+        Command::New { year, day, html } => {
+            let puzzle = match html.map(|path| fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.display(), err))) {
+                Some(Ok(html)) => Puzzle::from_html(&html),
+                Some(Err(err)) => {
+                    eprintln!("Error reading puzzle page: {}", err);
+                    process::exit(1);
+                }
+                None => Puzzle::default(),
+            };
+            let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("aoc lies in the workspace directory");
+            match scaffold::create(root, year, day, &puzzle) {
+                Ok(dir) => {
+                    println!("Created {}", dir.display());
+                    println!("Download its input with: cargo run -p aoc -- fetch {} {}", year, day);
+                }
+                Err(err) => {
+                    eprintln!("Error creating day: {}", err);
+                    process::exit(1);
+                }
+            }
+        }
+        Command::Watch { day, year, interval } => {
+            let year = year.or(config.year).unwrap_or_else(|| registry::solutions().last().map_or(0, |entry| entry.year));
+            if registry::find(year, day).is_none() {
+                eprintln!("Day {} of {} isn't implemented", day, year);
+                process::exit(1);
+            }
+            watch(&config, year, day, Duration::from_millis(interval));
+        }
+        Command::Test { year, day, update_snapshots } => {
+            if !test(&select(year, day), update_snapshots) {
+                process::exit(1);
+            }
+        }
+        Command::Repl { day, year, input } => {
+            let year = year.or(config.year).unwrap_or_else(|| registry::solutions().last().map_or(0, |entry| entry.year));
+            let Some(entry) = registry::find(year, day) else {
+                eprintln!("Day {} of {} isn't implemented", day, year);
+                process::exit(1);
+            };
+            let Some(start) = entry.repl else {
+                eprintln!("Day {} of {} has no interactive session", day, year);
+                process::exit(1);
+            };
+            let input = input.unwrap_or_else(|| config.input(year, day));
+            let mut session = input::get_file_string(&input)
+                .map_err(|err| format!("error reading file {}: {}", input.display(), err).into())
+                .and_then(|input| start(&input))
+                .unwrap_or_else(|err| {
+                    eprintln!("Error starting session: {}", err);
+                    process::exit(1);
+                });
+            println!("{} day {:02}, type help for the list of commands", year, day);
+            if let Err(err) = repl::run(session.as_mut(), &config.cache_dir().join("repl_history")) {
+                eprintln!("Error reading commands: {}", err);
+                process::exit(1);
+            }
+        }
+        Command::Examples { year, day, html } => {
+            let Some(entry) = registry::find(year, day) else {
+                eprintln!("Day {} of {} isn't implemented, create it with aoc new first", day, year);
+                process::exit(1);
+            };
+            let html = fs::read_to_string(&html).unwrap_or_else(|err| {
+                eprintln!("Error reading puzzle page: {}: {}", html.display(), err);
+                process::exit(1);
+            });
+            let examples = examples::extract(&html, Some(entry));
+            if examples.is_empty() {
+                eprintln!("No example answer found in the puzzle page");
+                process::exit(1);
+            }
+            let dir = registry::day_dir(year, day);
+            if let Err(err) = examples::write(&dir, (entry.type_name)(), &examples) {
+                eprintln!("Error writing examples: {}", err);
+                process::exit(1);
+            }
+            for example in &examples {
+                match example.confirmed {
+                    true => println!("Part {}: {}", example.part, example.answer),
+                    false => println!("Part {}: {}, not confirmed by the solution, check resources/test/part{}.txt",
+                                      example.part, example.answer, example.part),
+                }
+            }
+        }
+    }
+}
+
+/// Runs a day's tests and parts, then again after every change to its sources or inputs, forever
+fn watch(config: &Config, year: u16, day: u8, interval: Duration) -> ! {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("aoc lies in the workspace directory");
+    let dir = registry::day_dir(year, day);
+    let dirs = [dir.join("src"), dir.join("resources")];
+    println!("Watching {} and {}", dirs[0].display(), dirs[1].display());
+    let mut snapshot = Snapshot::take(&dirs);
+    let mut previous = Vec::new();
+    loop {
+        previous = watch_once(root, config, year, day, &previous);
+        loop {
+            thread::sleep(interval);
+            let newer = Snapshot::take(&dirs);
+            let changes: Vec<String> = snapshot.changes(&newer).iter().map(|path| path.display().to_string()).collect();
+            if !changes.is_empty() {
+                println!("\nChanged: {}", changes.join(", "));
+                snapshot = newer;
+                break;
+            }
+        }
+    }
+}
+
+/// Runs a day's tests, then its parts, printing how they compare with the previous ones
+fn watch_once(root: &Path, config: &Config, year: u16, day: u8, previous: &[Record]) -> Vec<Record> {
+    let package = registry::crate_name(year, day);
+    match watch::cargo(root, &["test", "-q", "-p", &package]) {
+        Ok(outcome) if outcome.success => println!("Tests: ok"),
+        Ok(outcome) => {
+            print!("{}", outcome.stdout);
+            eprint!("{}", outcome.stderr);
+            println!("Tests: FAILED");
+        }
+        Err(err) => eprintln!("Error running cargo test: {}", err),
+    }
+    let (year_arg, day_arg) = (year.to_string(), day.to_string());
+    let outcome = match watch::cargo(root, &["run", "-q", "-p", "aoc", "--", "run", &year_arg, &day_arg, "--format", "json"]) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            eprintln!("Error running cargo run: {}", err);
+            return previous.to_vec();
+        }
+    };
+    let Ok(records) = watch::parse_records(&outcome.stdout) else {
+        // Most likely a compilation error, which cargo prints to stderr
+        eprint!("{}", outcome.stderr);
+        return previous.to_vec();
+    };
+    let answers = Answers::load_file(&config.answers(year)).unwrap_or_else(|err| {
+        eprintln!("Error reading answers: {}", err);
+        Answers::default()
+    });
+    for record in &records {
+        let before = previous.iter().find(|before| before.part == record.part);
+        let expected = record.part.and_then(|part| answers.expected(day, part));
+        for line in watch::describe(record, before, expected.as_ref()) {
+            println!("{}", line);
+        }
+    }
+    records
+}
+
+/// Runs cargo test on the crates of entries, printing its output as it goes. Returns false on failures.
+fn test(entries: &[&Entry], update_snapshots: bool) -> bool {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("aoc lies in the workspace directory");
+    let mut cargo = process::Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    cargo.arg("test").current_dir(root);
+    for entry in entries {
+        cargo.args(["-p", &registry::crate_name(entry.year, entry.day)]);
+    }
+    if update_snapshots {
+        cargo.env(snapshot::UPDATE_VARIABLE, "1");
+    }
+    match cargo.status() {
+        Ok(status) => status.success(),
+        Err(err) => {
+            eprintln!("Error running cargo test: {}", err);
+            false
+        }
+    }
+}
+
+/// Answer cache of run when enabled by --cache, or else by aoc.toml unless --no-cache is given
+fn answer_cache(config: &Config, cache: bool, no_cache: bool) -> Option<AnswerCache> {
+    if !cache && (no_cache || config.cache_answers != Some(true)) {
+        return None;
+    }
+    Some(AnswerCache::new(AnswerCache::dir(&config.cache_dir()), cache::VERSION.to_string()))
+}
+
+/// Registered days of a year, or a single day, or every day. Exits if none match.
+fn select(year: Option<u16>, day: Option<u8>) -> Vec<&'static Entry> {
+    let entries: Vec<&Entry> = registry::solutions()
+        .iter()
+        .filter(|entry| year.is_none_or(|year| entry.year == year))
+        .filter(|entry| day.is_none_or(|day| entry.day == day))
+        .collect();
+    if entries.is_empty() {
+        eprintln!("No implemented day matches");
+        process::exit(1);
+    }
+    entries
+}
+
+/// Client for the site at base_url, or else AOC_BASE_URL, or else aoc.toml's, or else https://adventofcode.com
+fn client(base_url: Option<String>, config: &Config) -> Client {
+    let base_url = base_url
+        .or_else(|| env::var(fetch::BASE_URL_VARIABLE).ok())
+        .or_else(|| config.base_url.clone())
+        .unwrap_or_else(|| fetch::DEFAULT_BASE_URL.to_string());
+    let session = fetch::session_token(config.session_file.as_deref());
+    Client::new(&base_url, session, Throttle::in_cache(&config.cache_dir())).with_session_file(config.session_file.clone())
+}
+
+/// Answer to a part of a day computed from its personal input. Exits if it can't be submitted.
+fn solve(config: &Config, year: u16, day: u8, part: u8) -> String {
+    let Some(entry) = registry::find(year, day) else {
+        eprintln!("Day {} of {} isn't implemented, pass the answer with --answer", day, year);
+        process::exit(1);
+    };
+    let record = report::run(entry, &config.input(year, day), None)
+        .into_iter()
+        .find(|record| record.part.is_none() || record.part == Some(part))
+        .expect("report::run returns a record for each part or a single failure");
+    match (record.answer, record.error) {
+        (Some(answer), None) if !answer.contains('\n') => answer,
+        (Some(answer), None) => {
+            eprintln!("The answer spans several lines, read it and pass it with --answer:\n{}", answer);
+            process::exit(1);
+        }
+        (_, err) => {
+            eprintln!("Error solving part {}: {}", part, err.unwrap_or_default());
+            process::exit(1);
+        }
+    }
+}
+
+/// Submits an answer and records the outcome. Returns false unless the part ends up solved.
+fn submit(client: &Client, cache_dir: &Path, year: u16, day: u8, part: u8, answer: &str) -> bool {
+    let path = Submissions::path(cache_dir, year);
+    let mut submissions = match Submissions::load(&path) {
+        Ok(submissions) => submissions,
+        Err(err) => {
+            eprintln!("Error reading submissions: {}", err);
+            return false;
+        }
+    };
+    if let Some(known) = submissions.known_outcome(day, part, answer) {
+        println!("Not submitting {}: {} was {}", answer, known.answer, known.outcome);
+        return matches!(known.outcome, Outcome::Correct | Outcome::AlreadySolved);
+    }
+    if let Some(seconds) = submissions.seconds_to_wait() {
+        println!("Not submitting {}: wait {}s before submitting another answer", answer, seconds);
+        return false;
+    }
+    let outcome = match client.submit(year, day, part, answer) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            eprintln!("Error submitting answer: {}", err);
+            return false;
+        }
+    };
+    println!("{}: {}", answer, outcome);
+    let solved = matches!(outcome, Outcome::Correct | Outcome::AlreadySolved);
+    submissions.record(day, part, answer, outcome);
+    if let Err(err) = submissions.save(&path) {
+        eprintln!("Error writing submissions: {}", err);
+        return false;
+    }
+    solved
+}
+
+/// Runs and prints days against their input files. Returns false if any part couldn't be solved.
+fn run(days: &[(&Entry, PathBuf)], format: Format, cache: Option<&AnswerCache>) -> bool {
+    let mut records = Vec::new();
+    for (entry, file_name) in days {
+        let day_records = report::run(entry, file_name, cache);
+        match format {
+            Format::Text => print_text(entry, &day_records),
+            Format::Ndjson => {
+                for record in &day_records {
+                    println!("{}", serde_json::to_string(record).expect("records serialize to JSON"));
+                }
+            }
+            Format::Json => {}
+        }
+        records.extend(day_records);
+    }
+    if format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&records).expect("records serialize to JSON"));
+    }
+    !records.iter().any(Record::is_failure)
+}
+
+/**
+    Runs the parts of days concurrently on jobs threads, then prints a summary table, or their records
+    as JSON. NDJSON records are printed as each part completes. Returns false if any part couldn't be solved.
+*/
+fn run_all(days: &[(&Entry, PathBuf)], jobs: usize, format: Format, order: Order, cache: Option<&AnswerCache>) -> bool {
+    let start = Instant::now();
+    let records = report::run_parallel(days, jobs, cache, |records| {
+        if format == Format::Ndjson {
+            let lines: Vec<String> = records.iter().map(|record| serde_json::to_string(record).expect("records serialize to JSON")).collect();
+            println!("{}", lines.join("\n"));
+        }
+    });
+    match format {
+        Format::Text => {
+            print!("{}", report::table(&records, order));
+            println!("{} wall time with --jobs {}", bench::format_nanos(start.elapsed().as_nanos() as u64), jobs);
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records).expect("records serialize to JSON")),
+        Format::Ndjson => {}
+    }
+    !records.iter().any(Record::is_failure)
+}
+
+/// Multi-line answers (e.g. day 10's CRT) start on their own line. Errors are printed to stderr.
+fn print_text(entry: &Entry, records: &[Record]) {
+    println!("{} day {:02}", entry.year, entry.day);
+    for record in records {
+        let label = match record.part {
+            Some(1) => "First part",
+            Some(_) => "Second part",
+            None => "Error",
+        };
+        let label = if record.cached { format!("{} (cached)", label) } else { label.to_string() };
+        match (&record.answer, &record.error) {
+            (_, Some(err)) if record.part.is_none() => eprintln!("{} with file {}: {}", label, record.input, err),
+            (_, Some(err)) => eprintln!("{}: {}", label, err),
+            (Some(answer), None) if answer.contains('\n') => println!("{}:\n{}", label, answer),
+            (Some(answer), None) => println!("{}: {}", label, answer),
+            (None, None) => unreachable!("records hold either an answer or an error"),
+        }
+    }
+}
+
+/// Verifies every entry against its year's answers and prints a summary. Returns false on failures.
+fn verify(entries: &[&Entry], config: &Config) -> bool {
+    let mut answers_by_year = BTreeMap::new();
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+    for entry in entries {
+        let answers = match answers_by_year.entry(entry.year) {
+            btree_map::Entry::Occupied(occupied) => occupied.into_mut(),
+            btree_map::Entry::Vacant(vacant) => match Answers::load_file(&config.answers(entry.year)) {
+                Ok(answers) => vacant.insert(answers),
+                Err(err) => {
+                    eprintln!("Error reading answers: {}", err);
+                    return false;
+                }
+            },
+        };
+        for (part, verdict) in verify_day(entry, &config.input(entry.year, entry.day), answers).into_iter().enumerate() {
+            print!("{} day {:02} part {}: ", entry.year, entry.day, part + 1);
+            match &verdict {
+                Verdict::Pass => println!("ok"),
+                Verdict::Unknown(actual) => println!("no expected answer, found {}", actual.trim_end()),
+                Verdict::Failed(err) => println!("FAILED, {}", err),
+                Verdict::Mismatch { expected, actual } if !expected.contains('\n') && !actual.contains('\n') => {
+                    println!("MISMATCH, expected {}, found {}", expected, actual)
+                }
+                Verdict::Mismatch { expected, actual } => {
+                    println!("MISMATCH");
+                    for line in answers::diff(expected, actual) {
+                        println!("    {}", line);
+                    }
+                }
+            }
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Unknown(_) => unknown += 1,
+                _ => failed += 1,
+            }
+        }
+    }
+    println!("{} passed, {} failed, {} without expected answer", passed, failed, unknown);
+    failed == 0
+}
+
+/// Verdicts of both parts of a day. Input and parse errors fail both parts.
+fn verify_day(entry: &Entry, input: &Path, answers: &Answers) -> [Verdict; 2] {
+    let solution = input::get_file_string(input)
+        .map_err(|err| format!("error reading file {}: {}", input.display(), err).into())
+        .and_then(|input| (entry.parse)(&input));
+    match solution {
+        Err(err) => {
+            let message = err.to_string();
+            [Verdict::Failed(err), Verdict::Failed(message.into())]
+        }
+        Ok(solution) => [
+            Verdict::new(answers.expected(entry.day, 1), solution.part1()),
+            Verdict::new(answers.expected(entry.day, 2), solution.part2()),
+        ],
+    }
+}
+
+/**
+    Benchmarks every entry against its input and prints a table of the results, optionally
+    comparing them with a baseline and saving them as a new one. Returns false on errors or
+    regressions.
+*/
+fn benchmark(entries: &[&Entry], config: &Config, settings: Settings, baseline: Option<&Baseline>, threshold: f64, save: Option<&Path>) -> bool {
+    let mut results = Baseline::default();
+    let mut succeeded = true;
+    println!("{:<12} {:<6} {:>10} {:>10} {:>10}", "day", "phase", "min", "median", "p95");
+    for entry in entries {
+        let input = config.input(entry.year, entry.day);
+        let stats = input::get_file_string(&input)
+            .map_err(|err| format!("error reading file {}: {}", input.display(), err).into())
+            .and_then(|input| bench::bench(entry, &input, settings));
+        let stats = match stats {
+            Ok(stats) => stats,
+            Err(err) => {
+                eprintln!("{} day {:02}: {}", entry.year, entry.day, err);
+                succeeded = false;
+                continue;
+            }
+        };
+        for (phase, stats) in stats {
+            let label = format!("{} day {:02}", entry.year, entry.day);
+            print!("{:<12} {:<6} {:>10} {:>10} {:>10}", label, phase,
+                   bench::format_nanos(stats.min), bench::format_nanos(stats.median), bench::format_nanos(stats.p95));
+            let previous = baseline.and_then(|baseline| baseline.get(entry.year, entry.day, phase));
+            match previous.and_then(|previous| Some((previous, bench::regression(previous, &stats, threshold)?))) {
+                Some((previous, change)) => {
+                    println!("  REGRESSION +{:.1}% (was {})", change, bench::format_nanos(previous.median));
+                    succeeded = false;
+                }
+                None => println!(),
+            }
+            results.insert(entry.year, entry.day, phase, stats);
+        }
+    }
+    if let Some(path) = save {
+        if let Err(err) = results.save(path) {
+            eprintln!("Error writing baseline: {}", err);
+            succeeded = false;
+        }
+    }
+    succeeded
+}
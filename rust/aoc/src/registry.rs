@@ -0,0 +1,119 @@
+use std::any;
+use std::path::PathBuf;
+
+use solution::{parse_boxed, start_boxed, Error, Generator, Random, Repl, Solution};
+
+/// Parses a day's input into a type erased Solution
+pub type Parse = fn(&str) -> Result<Box<dyn Solution + Send + Sync>, Error>;
+
+/// Generates a random valid input of a day, see Generator
+pub type Generate = fn(&mut Random, usize) -> String;
+
+/// Parses a day's input into a type erased interactive session, see Repl
+pub type StartRepl = fn(&str) -> Result<Box<dyn Repl>, Error>;
+
+/// A registered day, able to parse its input into a type erased Solution and to generate inputs
+pub struct Entry {
+    pub year: u16,
+    pub day: u8,
+    pub parse: Parse,
+    pub generate: Generate,
+    /// Path of the day's model, e.g. "aoc_2022_day10::CathodeRayTube"
+    pub type_name: fn() -> &'static str,
+    /// Interactive session of the days that have one
+    pub repl: Option<StartRepl>,
+}
+
+impl Entry {
+    const fn new<S: Solution + Generator + Send + Sync + 'static>(year: u16, day: u8) -> Self {
+        Self { year, day, parse: parse_boxed::<S>, generate: S::generate, type_name: any::type_name::<S>, repl: None }
+    }
+
+    /// Lets aoc repl explore the day's input with R
+    const fn with_repl<R: Repl + 'static>(self) -> Self {
+        Self { repl: Some(start_boxed::<R>), ..self }
+    }
+
+    /// Path of the personal input shipped in the day's resources directory
+    pub fn default_input(&self) -> PathBuf {
+        input_path(self.year, self.day)
+    }
+}
+
+/// Name of the crate of a day, e.g. aoc-2022-day05, so that days of different years never clash
+pub fn crate_name(year: u16, day: u8) -> String {
+    format!("aoc-{}-day{:02}", year, day)
+}
+
+/// Directory of the crate of a day, whether it's implemented yet or not
+pub fn day_dir(year: u16, day: u8) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(year.to_string())
+        .join(format!("day{:02}", day))
+}
+
+/// Path of the personal input of a day, whether it's implemented yet or not
+pub fn input_path(year: u16, day: u8) -> PathBuf {
+    day_dir(year, day).join("resources/input/first_part.txt")
+}
+
+/// Every known solution ordered by (year, day). aoc new inserts the days it creates here.
+static SOLUTIONS: &[Entry] = &[
+    Entry::new::<aoc_2022_day01::CalorieCounting>(2022, 1),
+    Entry::new::<aoc_2022_day02::RockPaperScissors>(2022, 2),
+    Entry::new::<aoc_2022_day03::RucksackReorganization>(2022, 3),
+    Entry::new::<aoc_2022_day04::CampCleanup>(2022, 4),
+    Entry::new::<aoc_2022_day05::SupplyStacks>(2022, 5).with_repl::<aoc_2022_day05::SupplyStacksRepl>(),
+    Entry::new::<aoc_2022_day06::TuningTrouble>(2022, 6),
+    Entry::new::<aoc_2022_day07::NoSpaceLeftOnDevice>(2022, 7).with_repl::<aoc_2022_day07::NoSpaceLeftOnDeviceRepl>(),
+    Entry::new::<aoc_2022_day08::TreetopTreeHouse>(2022, 8),
+    Entry::new::<aoc_2022_day09::RopeBridge>(2022, 9),
+    Entry::new::<aoc_2022_day10::CathodeRayTube>(2022, 10).with_repl::<aoc_2022_day10::CathodeRayTubeRepl>(),
+];
+
+/// Enumerates every registered solution ordered by (year, day)
+pub fn solutions() -> &'static [Entry] {
+    SOLUTIONS
+}
+
+/// Looks up the solution of a given day
+pub fn find(year: u16, day: u8) -> Option<&'static Entry> {
+    SOLUTIONS.iter().find(|entry| entry.year == year && entry.day == day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(2022, 5).map(|entry| entry.day), Some(5));
+        assert!(find(2022, 26).is_none());
+        assert!(find(2021, 1).is_none());
+        assert_eq!(find(2022, 10).map(|entry| (entry.type_name)()), Some("aoc_2022_day10::CathodeRayTube"));
+    }
+
+    #[test]
+    fn test_solutions_are_ordered_and_unique() {
+        assert!(solutions()
+            .windows(2)
+            .all(|entries| (entries[0].year, entries[0].day) < (entries[1].year, entries[1].day)));
+    }
+
+    #[test]
+    fn test_generated_inputs_parse() {
+        for entry in solutions() {
+            let input = (entry.generate)(&mut solution::random(1), 20);
+            let solution = (entry.parse)(&input).unwrap_or_else(|err| panic!("{} day {}: {}", entry.year, entry.day, err));
+            assert!(solution.part1().is_ok() && solution.part2().is_ok(), "{} day {}", entry.year, entry.day);
+        }
+    }
+
+    #[test]
+    fn test_default_inputs_exist() {
+        for entry in solutions() {
+            assert!(entry.default_input().is_file(), "missing input for {} day {}", entry.year, entry.day);
+        }
+    }
+}
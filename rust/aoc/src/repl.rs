@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use solution::{Error, Repl};
+
+/// Commands understood by every session, besides the day's own
+const COMMON_COMMANDS: &[(&str, &str)] = &[
+    ("help", "lists the commands"),
+    ("quit", "ends the session, as do exit and Ctrl-D"),
+];
+
+/**
+    Runs a line typed in a session: blank lines do nothing, help and quit are handled here and
+    every other command is handed to the day. Returns what to print, or None to end the session.
+*/
+pub fn respond(session: &mut dyn Repl, line: &str) -> Option<Result<String, Error>> {
+    let mut words = line.split_whitespace();
+    let Some(command) = words.next() else {
+        return Some(Ok(String::new()));
+    };
+    let arguments: Vec<&str> = words.collect();
+    match command {
+        "quit" | "exit" => None,
+        "help" => Some(Ok(help(session.commands()))),
+        command => Some(session.execute(command, &arguments)),
+    }
+}
+
+/// One line per command, their usages aligned
+fn help(commands: &[(&str, &str)]) -> String {
+    let commands: Vec<&(&str, &str)> = commands.iter().chain(COMMON_COMMANDS).collect();
+    let width = commands.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+    commands
+        .iter()
+        .map(|(usage, description)| format!("{:<width$}  {}", usage, description, width = width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/**
+    Reads commands with line editing until quit or Ctrl-D, printing their output to stdout and
+    their errors to stderr. The history is loaded from and saved to history, shared by every day.
+*/
+pub fn run(session: &mut dyn Repl, history: &Path) -> Result<(), Error> {
+    let mut editor = DefaultEditor::new()?;
+    // A missing history only means that no session ran yet
+    let _ = editor.load_history(history);
+    loop {
+        let line = match editor.readline(&format!("{}> ", session.prompt())) {
+            Ok(line) => line,
+            // Ctrl-C only discards the line being typed, like in a shell
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        if !line.trim().is_empty() {
+            editor.add_history_entry(line.as_str())?;
+        }
+        match respond(session, &line) {
+            None => break,
+            Some(Ok(output)) if output.is_empty() => {}
+            Some(Ok(output)) => println!("{}", output.trim_end_matches('\n')),
+            Some(Err(err)) => eprintln!("Error: {}", err),
+        }
+    }
+    if let Some(dir) = history.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    editor.save_history(history)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solution::{count_argument, unknown_command};
+
+    struct Counter(usize);
+
+    impl Repl for Counter {
+        fn start(input: &str) -> Result<Self, Error> {
+            Ok(Self(input.len()))
+        }
+
+        fn commands(&self) -> &'static [(&'static str, &'static str)] {
+            &[("add [n]", "adds n, 1 by default")]
+        }
+
+        fn prompt(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn execute(&mut self, command: &str, arguments: &[&str]) -> Result<String, Error> {
+            match command {
+                "add" => self.0 += count_argument(arguments)?,
+                _ => return Err(unknown_command(command)),
+            }
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_respond() {
+        let mut counter = Counter(0);
+        assert_eq!(respond(&mut counter, "  add   2 ").map(Result::unwrap), Some("2".to_string()));
+        assert_eq!(respond(&mut counter, " ").map(Result::unwrap), Some(String::new()));
+        assert!(respond(&mut counter, "sub").unwrap().is_err());
+        assert!(respond(&mut counter, "quit").is_none());
+        assert!(respond(&mut counter, "exit").is_none());
+        assert_eq!(respond(&mut counter, "help").map(Result::unwrap), Some("\
+add [n]  adds n, 1 by default
+help     lists the commands
+quit     ends the session, as do exit and Ctrl-D".to_string()));
+    }
+}
@@ -0,0 +1,383 @@
+use std::cmp;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Instant;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use solution::{Answer, Error, Solution};
+use tracing::{info_span, warn};
+
+use crate::bench;
+use crate::cache::AnswerCache;
+use crate::registry::Entry;
+
+/// How the results of run are printed
+#[derive(ValueEnum, Deserialize, PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Human readable lines, e.g. "First part: 42"
+    #[default]
+    Text,
+    /// A single JSON array holding every record
+    Json,
+    /// One JSON record per line, printed as soon as it's computed
+    Ndjson,
+}
+
+/**
+    Outcome of one part of a day. Failing to read or parse the input yields a single record without
+    part, holding the error. duration_ns only covers the part itself, not reading or parsing.
+*/
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Record {
+    pub year: u16,
+    pub day: u8,
+    pub part: Option<u8>,
+    pub answer: Option<String>,
+    pub duration_ns: Option<u64>,
+    pub input: String,
+    pub error: Option<String>,
+    /// Whether the answer comes from the answer cache rather than from solving the part
+    #[serde(default)]
+    pub cached: bool,
+}
+
+impl Record {
+    fn new(entry: &Entry, input: &Path) -> Self {
+        Self {
+            year: entry.year,
+            day: entry.day,
+            part: None,
+            answer: None,
+            duration_ns: None,
+            input: input.display().to_string(),
+            error: None,
+            cached: false,
+        }
+    }
+
+    fn failure(entry: &Entry, input: &Path, error: String) -> Self {
+        Self { error: Some(error), ..Self::new(entry, input) }
+    }
+
+    fn part(entry: &Entry, input: &Path, part: u8, solve: impl FnOnce() -> Result<Answer, Error>) -> Self {
+        let _span = info_span!("part", part).entered();
+        let start = Instant::now();
+        let answer = catch_panic(solve);
+        let duration_ns = Some(start.elapsed().as_nanos() as u64);
+        let (answer, error) = match answer {
+            Ok(Ok(answer)) => (Some(answer.to_string()), None),
+            Ok(Err(err)) => (None, Some(err.to_string())),
+            Err(panic) => (None, Some(panic)),
+        };
+        Self { part: Some(part), answer, duration_ns, error, ..Self::new(entry, input) }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/**
+    Runs both parts of a day against an input file, - being the standard input. A panic while parsing
+    or solving is caught and reported as an error, so that it can't take other days down with it.
+    With a cache, answers known for the same input are returned without even parsing it, as long as
+    both parts are known, and new answers are stored.
+*/
+pub fn run(entry: &Entry, input: &Path, cache: Option<&AnswerCache>) -> Vec<Record> {
+    let prepared = OnceLock::new();
+    [1, 2].into_iter().flat_map(|part| run_part(entry, input, part, &prepared, cache)).collect()
+}
+
+/// A day's input once read and parsed, shared by the jobs solving its parts
+enum Prepared {
+    /// Reading or parsing failed, reported by the first part alone
+    Failed(Record),
+    /// Records of both parts, answered by the cache
+    Cached(Vec<Record>),
+    Parsed { text: String, solution: Box<dyn Solution + Send + Sync> },
+}
+
+/**
+    Records of one part of a day, preparing its input first unless the other part already did. A
+    failure to read or parse yields a single record without part, returned for the first part.
+*/
+fn run_part(entry: &Entry, input: &Path, part: u8, prepared: &OnceLock<Prepared>, cache: Option<&AnswerCache>) -> Vec<Record> {
+    let _span = info_span!("day", year = entry.year, day = entry.day).entered();
+    match prepared.get_or_init(|| prepare(entry, input, cache)) {
+        Prepared::Failed(record) if part == 1 => vec![record.clone()],
+        Prepared::Failed(_) => Vec::new(),
+        Prepared::Cached(records) => records.iter().filter(|record| record.part == Some(part)).cloned().collect(),
+        Prepared::Parsed { text, solution } => {
+            let record = Record::part(entry, input, part, || if part == 1 { solution.part1() } else { solution.part2() });
+            if let (Some(cache), Some(answer)) = (cache, &record.answer) {
+                if let Err(err) = cache.insert(entry.year, entry.day, part, text, answer) {
+                    warn!("cannot cache answer: {}", err);
+                }
+            }
+            vec![record]
+        }
+    }
+}
+
+fn prepare(entry: &Entry, input: &Path, cache: Option<&AnswerCache>) -> Prepared {
+    let text = match input::get_file_string(input) {
+        Err(err) => return Prepared::Failed(Record::failure(entry, input, format!("cannot read input: {}", err))),
+        Ok(text) => text,
+    };
+    if let Some(records) = cache.and_then(|cache| cached(entry, input, &text, cache)) {
+        return Prepared::Cached(records);
+    }
+    match catch_panic(|| (entry.parse)(&text)).and_then(|parsed| parsed.map_err(|err| err.to_string())) {
+        Err(err) => Prepared::Failed(Record::failure(entry, input, format!("cannot parse input: {}", err))),
+        Ok(solution) => Prepared::Parsed { text, solution },
+    }
+}
+
+/// Records of both parts if the cache knows their answers for text. Unreadable caches are ignored.
+fn cached(entry: &Entry, input: &Path, text: &str, cache: &AnswerCache) -> Option<Vec<Record>> {
+    [1, 2]
+        .into_iter()
+        .map(|part| {
+            let answer = cache.get(entry.year, entry.day, part, text).unwrap_or_else(|err| {
+                warn!("cannot read cached answer: {}", err);
+                None
+            })?;
+            Some(Record { part: Some(part), answer: Some(answer), cached: true, ..Record::new(entry, input) })
+        })
+        .collect()
+}
+
+/// Calls f, turning a panic into an error holding its message
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        format!("panicked: {}", message)
+    })
+}
+
+/**
+    Runs days against their input files on jobs threads, each taking the next part of a day as soon
+    as it's done with one, so that both parts of a slow day are solved at once. The part taken first
+    reads and parses the input, the other one waits for it and shares the parsed solution. done is
+    called with the records of each part as it completes, while the returned records follow the
+    order of days.
+*/
+pub fn run_parallel(days: &[(&Entry, PathBuf)], jobs: usize, cache: Option<&AnswerCache>, done: impl Fn(&[Record]) + Sync) -> Vec<Record> {
+    let next = AtomicUsize::new(0);
+    let prepared: Vec<OnceLock<Prepared>> = days.iter().map(|_| OnceLock::new()).collect();
+    // Job index is day index * 2 + part - 1
+    let job_count = days.len() * 2;
+    let results: Mutex<Vec<Vec<Record>>> = Mutex::new((0..job_count).map(|_| Vec::new()).collect());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, job_count.max(1)) {
+            scope.spawn(|| {
+                let mut index = next.fetch_add(1, Ordering::Relaxed);
+                while let Some((entry, input)) = days.get(index / 2) {
+                    let records = run_part(entry, input, (index % 2 + 1) as u8, &prepared[index / 2], cache);
+                    if !records.is_empty() {
+                        done(&records);
+                    }
+                    results.lock().expect("no thread panics while holding the lock")[index] = records;
+                    index = next.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    results.into_inner().expect("no thread panics while holding the lock").into_iter().flatten().collect()
+}
+
+/// Order of the rows of the summary table
+#[derive(ValueEnum, PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum Order {
+    /// By year, day and part
+    #[default]
+    Day,
+    /// Slowest parts first, failures to read or parse last
+    Duration,
+}
+
+/**
+    Table of records with their status, answer and duration. Multi-line answers, such as day 10's
+    CRT, continue on the following lines of the answer column.
+*/
+pub fn table(records: &[Record], order: Order) -> String {
+    let mut rows: Vec<&Record> = records.iter().collect();
+    match order {
+        Order::Day => rows.sort_by_key(|record| (record.year, record.day, record.part)),
+        Order::Duration => rows.sort_by_key(|record| cmp::Reverse(record.duration_ns)),
+    }
+    let answer_width = records.iter()
+        .flat_map(|record| record.answer.as_deref().or(record.error.as_deref()).unwrap_or_default().lines())
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("answer".len());
+    let mut table = format!("{:<12} {:<4} {:<6} {:<width$} {:>10}\n", "day", "part", "status", "answer", "time", width = answer_width);
+    for record in rows {
+        let label = format!("{} day {:02}", record.year, record.day);
+        let part = record.part.map_or("-".to_string(), |part| part.to_string());
+        let status = match (record.is_failure(), record.cached) {
+            (true, _) => "FAILED",
+            (false, true) => "cached",
+            (false, false) => "ok",
+        };
+        let time = record.duration_ns.map_or("-".to_string(), bench::format_nanos);
+        let text = record.answer.as_deref().or(record.error.as_deref()).unwrap_or_default();
+        let mut lines = text.lines();
+        let first = lines.next().unwrap_or_default();
+        table.push_str(&format!("{:<12} {:<4} {:<6} {:<width$} {:>10}\n", label, part, status, first, time, width = answer_width));
+        for line in lines {
+            table.push_str(&format!("{:<25}{}\n", "", line));
+        }
+    }
+    let failures = records.iter().filter(|record| record.is_failure()).count();
+    let total: u64 = records.iter().filter_map(|record| record.duration_ns).sum();
+    table.push_str(&format!("{} parts, {} failed, {} solving\n", records.len(), failures, bench::format_nanos(total)));
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+    use crate::registry;
+    use solution::Solution;
+    use std::fs;
+
+    #[test]
+    fn test_run() {
+        let entry = registry::find(2022, 10).unwrap();
+        let records = run(entry, &entry.default_input(), None);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].part, Some(1));
+        assert_eq!(records[0].answer.as_deref(), Some("15260"));
+        assert_eq!(records[1].answer.as_ref().map(|answer| answer.lines().count()), Some(6));
+        assert!(records.iter().all(|record| !record.is_failure() && record.duration_ns.is_some()));
+    }
+
+    #[test]
+    fn test_run_failures() {
+        let entry = registry::find(2022, 5).unwrap();
+        let records = run(entry, Path::new("missing.txt"), None);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].part, None);
+        assert!(records[0].error.as_deref().unwrap().starts_with("cannot read input"));
+
+        let entry = registry::find(2022, 10).unwrap();
+        let records = run(entry, &registry::find(2022, 5).unwrap().default_input(), None);
+        assert!(records[0].error.as_deref().unwrap().starts_with("cannot parse input: line 1"));
+    }
+
+    #[test]
+    fn test_run_catches_panics() {
+        let entry = Entry {
+            year: 2022,
+            day: 1,
+            parse: |input| if input.is_empty() { panic!("empty input") } else { Ok(Box::new(aoc_2022_day01::CalorieCounting::parse(input)?)) },
+            generate: |_, _| String::new(),
+            type_name: || "panicking",
+            repl: None,
+        };
+        let records = run(&entry, Path::new("/dev/null"), None);
+        assert_eq!(records[0].error.as_deref(), Some("cannot parse input: panicked: empty input"));
+
+        let entry = Entry { parse: |_| Ok(Box::new(Panicking)), ..entry };
+        let records = run(&entry, &registry::find(2022, 1).unwrap().default_input(), None);
+        assert_eq!(records[0].answer.as_deref(), Some("1"));
+        assert_eq!(records[1].error.as_deref(), Some("panicked: part 2 is broken"));
+    }
+
+    #[test]
+    fn test_run_cached() {
+        let dir = temp_dir("report-cache");
+        let cache = AnswerCache::new(dir.clone(), "1".to_string());
+        let entry = Entry { parse: |_| Ok(Box::new(Panicking)), ..*registry::find(2022, 1).unwrap() };
+        let input = dir.join("input.txt");
+        fs::write(&input, "1000\n").unwrap();
+        assert!(!run(&entry, &input, Some(&cache))[0].cached);
+        assert_eq!(cache.get(2022, 1, 1, "1000\n").unwrap(), Some("1".to_string()));
+        // Part 2 has no answer to cache, so the day is solved again
+        assert!(!run(&entry, &input, Some(&cache))[0].cached);
+
+        cache.insert(2022, 1, 2, "1000\n", "2").unwrap();
+        let records = run(&entry, &input, Some(&cache));
+        assert!(records.iter().all(|record| record.cached && record.duration_ns.is_none()));
+        assert_eq!(records[1].answer.as_deref(), Some("2"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    struct Panicking;
+
+    impl Solution for Panicking {
+        fn parse(_: &str) -> Result<Self, Error> {
+            Ok(Self)
+        }
+
+        fn part1(&self) -> Result<Answer, Error> {
+            Ok(Answer::Number(1))
+        }
+
+        fn part2(&self) -> Result<Answer, Error> {
+            // Formatted, so that the payload is a String rather than a &str
+            panic!("part {} is broken", 2)
+        }
+    }
+
+    #[test]
+    fn test_run_parallel() {
+        let mut days: Vec<(&Entry, PathBuf)> = registry::solutions().iter().map(|entry| (entry, entry.default_input())).collect();
+        // A day failing to read its input is reported once, not once per part
+        days.insert(1, (registry::find(2022, 5).unwrap(), PathBuf::from("missing.txt")));
+        let done = AtomicUsize::new(0);
+        let records = run_parallel(&days, 4, None, |records| {
+            done.fetch_add(records.len(), Ordering::Relaxed);
+        });
+        assert_eq!(done.into_inner(), records.len());
+        let sequential: Vec<Record> = days.iter().flat_map(|(entry, input)| run(entry, input, None)).collect();
+        let answers = |records: &[Record]| records.iter().map(|record| (record.day, record.part, record.answer.clone(), record.error.clone())).collect::<Vec<_>>();
+        assert_eq!(answers(&records), answers(&sequential));
+    }
+
+    #[test]
+    fn test_table() {
+        let entry = registry::find(2022, 10).unwrap();
+        let records = vec![
+            Record { duration_ns: Some(10), ..Record::part(entry, Path::new("-"), 1, || Ok(Answer::Number(42))) },
+            Record { duration_ns: Some(2_000), ..Record::part(entry, Path::new("-"), 2, || Ok("#.\n.#".into())) },
+            Record::failure(registry::find(2022, 5).unwrap(), Path::new("-"), "cannot read input".to_string()),
+        ];
+        let by_day: Vec<String> = table(&records, Order::Day).lines().map(str::trim_end).map(String::from).collect();
+        assert_eq!(by_day, vec![
+            "day          part status answer                  time",
+            "2022 day 05  -    FAILED cannot read input          -",
+            "2022 day 10  1    ok     42                      10ns",
+            "2022 day 10  2    ok     #.                     2.0µs",
+            "                         .#",
+            "3 parts, 1 failed, 2.0µs solving",
+        ]);
+        let by_duration = table(&records, Order::Duration);
+        let days: Vec<&str> = by_duration.lines().skip(1).take(4).map(|line| &line[..16]).collect();
+        assert_eq!(days, vec!["2022 day 10  2  ", "                ", "2022 day 10  1  ", "2022 day 05  -  "]);
+    }
+
+    #[test]
+    fn test_record_json() {
+        let entry = registry::find(2022, 10).unwrap();
+        let record = Record::part(entry, Path::new("-"), 2, || Ok("#.\n.#\n".into()));
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["year"], 2022);
+        assert_eq!(json["part"], 2);
+        assert_eq!(json["answer"], "#.\n.#\n");
+        assert_eq!(json["input"], "-");
+        assert!(json["error"].is_null());
+    }
+}
@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use solution::Error;
+
+use crate::examples::{self, Example};
+use crate::html;
+use crate::registry;
+
+const CARGO_TEMPLATE: &str = include_str!("../templates/day/Cargo.toml.template");
+const FUZZ_TARGET_TEMPLATE: &str = include_str!("../templates/day/fuzz_target.rs.template");
+const README_TEMPLATE: &str = include_str!("../templates/day/README.md.template");
+const LIB_TEMPLATE: &str = include_str!("../templates/day/lib.rs.template");
+
+/// What a locally saved puzzle page tells about a day
+#[derive(Default, PartialEq, Debug)]
+pub struct Puzzle {
+    /// e.g. "Cathode-Ray Tube"
+    pub title: Option<String>,
+    /// Text of the first <pre> block, which usually holds the example input
+    pub example: Option<String>,
+    /// Examples paired with the answers the page already shows, see examples::extract
+    pub examples: Vec<Example>,
+}
+
+impl Puzzle {
+    /// Reads the title from the first <h2>, e.g. "--- Day 10: Cathode-Ray Tube ---"
+    pub fn from_html(html: &str) -> Self {
+        let title = html::elements(html, "h2").first().and_then(|heading| {
+            let heading = html::text(heading);
+            let (_, title) = heading.trim().trim_matches('-').split_once(": ")?;
+            Some(title.trim().to_string())
+        });
+        let example = html::elements(html, "pre").first().map(|example| html::text(example));
+        Self { title, example, examples: examples::extract(html, None) }
+    }
+}
+
+/// Name of a day's model derived from its title, e.g. "Cathode-Ray Tube" gives CathodeRayTube
+pub fn type_name(title: &str) -> Option<String> {
+    let name: String = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase()).into_iter().chain(chars)
+        })
+        .collect();
+    name.starts_with(|c: char| c.is_ascii_alphabetic()).then_some(name)
+}
+
+/**
+    Creates the crate of a day below root, the rust workspace directory, and registers it in the
+    workspace members, the dependencies of aoc and its registry. Its fuzz target is added to the
+    fuzz crate as well. Examples with known answers get tests, written by examples::write. Every
+    file is checked before any is written, so that a failure leaves the workspace untouched. The day's directory may already hold the input downloaded by aoc fetch,
+    which is kept. Returns the day's directory.
+*/
+pub fn create(root: &Path, year: u16, day: u8, puzzle: &Puzzle) -> Result<PathBuf, Error> {
+    let crate_name = registry::crate_name(year, day);
+    let member = format!("{}/day{:02}", year, day);
+    let dir = root.join(&member);
+    if dir.exists() && !holds_only_input(&dir) {
+        return Err(format!("{} already exists", dir.display()).into());
+    }
+    let name = puzzle.title.as_deref().and_then(type_name).unwrap_or_else(|| format!("Day{:02}", day));
+    let heading = match &puzzle.title {
+        Some(title) => format!("Day {}: {}", day, title),
+        None => format!("Day {}", day),
+    };
+    let path = format!("{}::{}", crate_name.replace('-', "_"), name);
+    let fuzz_target = format!("fuzz_targets/{}/day{:02}.rs", year, day);
+
+    let workspace_path = root.join("Cargo.toml");
+    let aoc_path = root.join("aoc/Cargo.toml");
+    let registry_path = root.join("aoc/src/registry.rs");
+    let workspace = add_member(&read(&workspace_path)?, &member)
+        .map_err(|err| format!("{}: {}", workspace_path.display(), err))?;
+    let aoc = add_dependency(&read(&aoc_path)?, &crate_name, &format!("../{}", member))
+        .map_err(|err| format!("{}: {}", aoc_path.display(), err))?;
+    let registry = add_entry(&read(&registry_path)?, year, day, &path)
+        .map_err(|err| format!("{}: {}", registry_path.display(), err))?;
+    let fuzz_path = root.join("fuzz/Cargo.toml");
+    let fuzz = add_dependency(&read(&fuzz_path)?, &crate_name, &format!("../{}", member))
+        .and_then(|fuzz| add_fuzz_target(&fuzz, &format!("{}-day{:02}", year, day), &fuzz_target))
+        .map_err(|err| format!("{}: {}", fuzz_path.display(), err))?;
+
+    let render = |template: &str| {
+        template
+            .replace("{{crate}}", &crate_name)
+            .replace("{{dir}}", &member)
+            .replace("{{name}}", &name)
+            .replace("{{path}}", &path)
+            .replace("{{heading}}", &heading)
+            .replace("{{year}}", &year.to_string())
+            .replace("{{day}}", &day.to_string())
+    };
+    fs::create_dir_all(dir.join("src"))?;
+    fs::create_dir_all(dir.join("resources/input"))?;
+    fs::create_dir_all(dir.join("resources/test"))?;
+    fs::write(dir.join(".gitignore"), "target/")?;
+    fs::write(dir.join("Cargo.toml"), render(CARGO_TEMPLATE))?;
+    fs::write(dir.join("README.md"), render(README_TEMPLATE))?;
+    fs::write(dir.join("src/lib.rs"), render(LIB_TEMPLATE))?;
+    // An empty input stands for one not fetched yet, which aoc fetch downloads over
+    let input = dir.join("resources/input/first_part.txt");
+    if !input.exists() {
+        fs::write(input, "")?;
+    }
+    fs::write(dir.join("resources/test/example.txt"), puzzle.example.as_deref().unwrap_or_default())?;
+    if !puzzle.examples.is_empty() {
+        examples::write(&dir, &path, &puzzle.examples)?;
+    }
+    fs::create_dir_all(root.join("fuzz").join(&fuzz_target).parent().expect("fuzz targets lie in a directory"))?;
+    fs::write(root.join("fuzz").join(&fuzz_target), render(FUZZ_TARGET_TEMPLATE))?;
+
+    fs::write(&workspace_path, workspace)?;
+    fs::write(&aoc_path, aoc)?;
+    fs::write(&registry_path, registry)?;
+    fs::write(&fuzz_path, fuzz)?;
+    Ok(dir)
+}
+
+/// Whether dir holds nothing but resources/input, as left by aoc fetch before the day is created
+fn holds_only_input(dir: &Path) -> bool {
+    let names = |dir: &Path| -> Vec<_> {
+        fs::read_dir(dir).into_iter().flatten().flatten().map(|entry| entry.file_name()).collect()
+    };
+    names(dir) == ["resources"] && names(&dir.join("resources")) == ["input"]
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err).into())
+}
+
+/// Adds "2022/day11" to the members of a workspace manifest, after the other days
+fn add_member(manifest: &str, member: &str) -> Result<String, Error> {
+    insert_sorted(manifest, "members = [", format!("    \"{}\",", member), |line| {
+        let member = line.trim().trim_end_matches(',').trim_matches('"');
+        member.starts_with(|c: char| c.is_ascii_digit()).then(|| member.to_string())
+    })
+}
+
+/// Adds "aoc-2022-day11 = { path = ... }" to the dependencies of a manifest, after the other days
+fn add_dependency(manifest: &str, crate_name: &str, path: &str) -> Result<String, Error> {
+    insert_sorted(manifest, "[dependencies]", format!("{} = {{ path = \"{}\" }}", crate_name, path), |line| {
+        let (name, _) = line.split_once(" = ")?;
+        name.starts_with("aoc-").then(|| name.to_string())
+    })
+}
+
+/// Adds "Entry::new::<aoc_2022_day11::MonkeyInTheMiddle>(2022, 11)," to the registered solutions
+fn add_entry(registry: &str, year: u16, day: u8, path: &str) -> Result<String, Error> {
+    insert_sorted(registry, "static SOLUTIONS", format!("    Entry::new::<{}>({}, {}),", path, year, day), |line| {
+        let arguments = line.trim().strip_prefix("Entry::new::<")?.split_once(">(")?.1;
+        let (year, day) = arguments.split_once(')')?.0.split_once(", ")?;
+        Some((year.parse::<u16>().ok()?, day.parse::<u8>().ok()?))
+    })
+}
+
+/**
+    Adds the [[bin]] section of a fuzz target to the fuzz manifest, keeping the sections of the
+    targets ordered by name, e.g. "2022-day11" for "fuzz_targets/2022/day11.rs".
+*/
+fn add_fuzz_target(manifest: &str, name: &str, path: &str) -> Result<String, Error> {
+    let section = format!("[[bin]]\nname = \"{}\"\npath = \"{}\"\ntest = false\ndoc = false\nbench = false", name, path);
+    let mut sections: Vec<&str> = manifest.trim_end().split("\n\n").collect();
+    let target_name = |section: &str| {
+        let name = section.strip_prefix("[[bin]]\n")?.lines().find_map(|line| line.strip_prefix("name = "))?;
+        Some(name.trim_matches('"').to_string())
+    };
+    if sections.iter().any(|section| target_name(section).as_deref() == Some(name)) {
+        return Err(format!("fuzz target {} is already there", name).into());
+    }
+    let position = sections.iter()
+        .position(|section| target_name(section).is_some_and(|other| other.as_str() > name))
+        .or_else(|| sections.iter().rposition(|section| target_name(section).is_some()).map(|index| index + 1))
+        .unwrap_or(sections.len());
+    sections.insert(position, &section);
+    let mut manifest = sections.join("\n\n");
+    manifest.push('\n');
+    Ok(manifest)
+}
+
+/**
+    Inserts line into the block of lines following the first one containing start, up to a blank
+    line, a section header or a closing bracket. The lines of the block for which key returns a
+    value are kept ordered by it, and their keys must stay unique.
+*/
+fn insert_sorted<K: Ord>(text: &str, start: &str, line: String, key: impl Fn(&str) -> Option<K>) -> Result<String, Error> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    let block_start = lines.iter().position(|line| line.contains(start)).ok_or_else(|| format!("no {} found", start))? + 1;
+    let block_end = block_start + lines[block_start..]
+        .iter()
+        .position(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('[') || line.starts_with(']')
+        })
+        .unwrap_or(lines.len() - block_start);
+    let new_key = key(&line).expect("the inserted line has a key");
+    let mut greater = None;
+    let mut last_keyed = None;
+    for (index, existing_line) in lines.iter().enumerate().take(block_end).skip(block_start) {
+        match key(existing_line) {
+            Some(existing) if existing == new_key => return Err(format!("{} is already there", line.trim()).into()),
+            Some(existing) => {
+                if existing > new_key && greater.is_none() {
+                    greater = Some(index);
+                }
+                last_keyed = Some(index);
+            }
+            None => {}
+        }
+    }
+    let position = greater.or(last_keyed.map(|index| index + 1)).unwrap_or(block_end);
+    lines.insert(position, &line);
+    let mut text = lines.join("\n");
+    text.push('\n');
+    Ok(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+
+    const PAGE: &str = "<main><article class=\"day-desc\"><h2>--- Day 10: Cathode-Ray Tube ---</h2>\
+        <p>For example:</p><pre><code>noop\naddx 3\naddx -5\n</code></pre>\
+        <pre><code><em>other</em></code></pre></article></main>";
+
+    #[test]
+    fn test_puzzle_from_html() {
+        let puzzle = Puzzle::from_html(PAGE);
+        assert_eq!(puzzle.title.as_deref(), Some("Cathode-Ray Tube"));
+        assert_eq!(puzzle.example.as_deref(), Some("noop\naddx 3\naddx -5\n"));
+        assert_eq!(puzzle.examples.iter().map(|example| (example.part, example.answer.as_str())).collect::<Vec<_>>(), vec![(1, "other")]);
+        assert_eq!(Puzzle::from_html("<p>Not found</p>"), Puzzle::default());
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("Cathode-Ray Tube").as_deref(), Some("CathodeRayTube"));
+        assert_eq!(type_name("No Space Left On Device").as_deref(), Some("NoSpaceLeftOnDevice"));
+        assert_eq!(type_name("2048"), None);
+        assert_eq!(type_name(""), None);
+    }
+
+    #[test]
+    fn test_insertions() {
+        let manifest = "members = [\n    \"aoc\",\n    \"2022/day01\",\n    \"2022/day03\",\n]\n";
+        assert_eq!(add_member(manifest, "2022/day02").unwrap(),
+                   "members = [\n    \"aoc\",\n    \"2022/day01\",\n    \"2022/day02\",\n    \"2022/day03\",\n]\n");
+        assert_eq!(add_member(manifest, "2022/day04").unwrap(),
+                   "members = [\n    \"aoc\",\n    \"2022/day01\",\n    \"2022/day03\",\n    \"2022/day04\",\n]\n");
+        assert!(add_member(manifest, "2022/day03").is_err());
+
+        let manifest = "[dependencies]\nclap = { workspace = true }\naoc-2022-day01 = { path = \"../2022/day01\" }\n\n[lints]\n";
+        assert_eq!(add_dependency(manifest, "aoc-2021-day25", "../2021/day25").unwrap(),
+                   "[dependencies]\nclap = { workspace = true }\naoc-2021-day25 = { path = \"../2021/day25\" }\naoc-2022-day01 = { path = \"../2022/day01\" }\n\n[lints]\n");
+        assert!(add_dependency(manifest, "aoc-2022-day01", "../2022/day01").is_err());
+
+        let registry = "static SOLUTIONS: &[Entry] = &[\n    Entry::new::<day01::A>(2022, 1),\n    Entry::new::<day10::B>(2022, 10).with_repl::<day10::R>(),\n];\n";
+        assert_eq!(add_entry(registry, 2022, 2, "day02::C").unwrap(),
+                   "static SOLUTIONS: &[Entry] = &[\n    Entry::new::<day01::A>(2022, 1),\n    Entry::new::<day02::C>(2022, 2),\n    Entry::new::<day10::B>(2022, 10).with_repl::<day10::R>(),\n];\n");
+        assert!(add_entry(registry, 2022, 10, "day10::C").is_err());
+        assert!(add_entry("fn main() {}\n", 2022, 10, "day10::C").is_err());
+
+        let bin = |name: &str| format!("[[bin]]\nname = \"{}\"\npath = \"fuzz_targets/{}.rs\"\ntest = false\ndoc = false\nbench = false\n", name, name);
+        let manifest = format!("[dependencies]\nsolution = {{ path = \"../solution\" }}\n\n{}\n{}", bin("2022-day01"), bin("2022-day03"));
+        let added = add_fuzz_target(&manifest, "2022-day02", "fuzz_targets/2022-day02.rs").unwrap();
+        assert_eq!(added, format!("[dependencies]\nsolution = {{ path = \"../solution\" }}\n\n{}\n{}\n{}",
+                                  bin("2022-day01"), bin("2022-day02"), bin("2022-day03")));
+        assert!(add_fuzz_target(&manifest, "2022-day04", "fuzz_targets/2022-day04.rs").unwrap().ends_with(&format!("\n\n{}", bin("2022-day04"))));
+        assert_eq!(add_fuzz_target("[package]\n", "2022-day01", "fuzz_targets/2022-day01.rs").unwrap(), format!("[package]\n\n{}", bin("2022-day01")));
+        assert!(add_fuzz_target(&manifest, "2022-day03", "fuzz_targets/2022-day03.rs").is_err());
+    }
+
+    #[test]
+    fn test_create() {
+        let root = temp_dir("scaffold");
+        fs::create_dir_all(root.join("aoc/src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\n    \"aoc\",\n]\n").unwrap();
+        fs::write(root.join("aoc/Cargo.toml"), "[package]\nname = \"aoc\"\n\n[dependencies]\nsolution = { path = \"../solution\" }\n").unwrap();
+        fs::write(root.join("aoc/src/registry.rs"), "static SOLUTIONS: &[Entry] = &[\n];\n").unwrap();
+        fs::create_dir_all(root.join("fuzz")).unwrap();
+        fs::write(root.join("fuzz/Cargo.toml"), "[package]\nname = \"aoc-fuzz\"\n\n[dependencies]\nlibfuzzer-sys = \"0.4\"\n").unwrap();
+
+        let dir = create(&root, 2022, 10, &Puzzle::from_html(PAGE)).unwrap();
+        assert_eq!(dir, root.join("2022/day10"));
+        assert!(read(&dir.join("Cargo.toml")).unwrap().contains("name = \"aoc-2022-day10\""));
+        assert!(read(&dir.join("README.md")).unwrap().contains("--input 2022/day10/resources/input/first_part.txt"));
+        assert!(read(&dir.join("README.md")).unwrap().contains("# Day 10: Cathode-Ray Tube"));
+        assert!(read(&dir.join("src/lib.rs")).unwrap().contains("pub struct CathodeRayTube {"));
+        assert_eq!(read(&dir.join("resources/test/example.txt")).unwrap(), "noop\naddx 3\naddx -5\n");
+        assert_eq!(read(&dir.join("resources/test/part1.answer")).unwrap(), "other\n");
+        assert!(read(&dir.join("tests/examples.rs")).unwrap().contains("use aoc_2022_day10::CathodeRayTube;"));
+        assert_eq!(read(&dir.join("resources/input/first_part.txt")).unwrap(), "");
+        assert!(read(&root.join("Cargo.toml")).unwrap().contains("    \"aoc\",\n    \"2022/day10\",\n]"));
+        assert!(read(&root.join("aoc/Cargo.toml")).unwrap().ends_with("aoc-2022-day10 = { path = \"../2022/day10\" }\n"));
+        assert!(read(&root.join("aoc/src/registry.rs")).unwrap().contains("    Entry::new::<aoc_2022_day10::CathodeRayTube>(2022, 10),\n];"));
+        assert!(read(&root.join("fuzz/fuzz_targets/2022/day10.rs")).unwrap().contains("solution::fuzz::<aoc_2022_day10::CathodeRayTube>(bytes)"));
+        let fuzz = read(&root.join("fuzz/Cargo.toml")).unwrap();
+        assert!(fuzz.contains("libfuzzer-sys = \"0.4\"\naoc-2022-day10 = { path = \"../2022/day10\" }\n"), "{}", fuzz);
+        assert!(fuzz.ends_with("\n\n[[bin]]\nname = \"2022-day10\"\npath = \"fuzz_targets/2022/day10.rs\"\ntest = false\ndoc = false\nbench = false\n"), "{}", fuzz);
+
+        assert!(create(&root, 2022, 10, &Puzzle::default()).unwrap_err().to_string().ends_with("already exists"));
+        // The dependency is already there, so nothing of day 11 must be written
+        fs::write(root.join("aoc/Cargo.toml"), "[dependencies]\naoc-2022-day11 = { path = \"../elsewhere\" }\n").unwrap();
+        assert!(create(&root, 2022, 11, &Puzzle::default()).is_err());
+        assert!(!root.join("2022/day11").exists());
+
+        // aoc fetch may come first and leave the input alone in the day's directory
+        fs::create_dir_all(root.join("2022/day12/resources/input")).unwrap();
+        fs::write(root.join("2022/day12/resources/input/first_part.txt"), "1\n").unwrap();
+        let dir = create(&root, 2022, 12, &Puzzle::default()).unwrap();
+        assert_eq!(read(&dir.join("resources/input/first_part.txt")).unwrap(), "1\n");
+        assert!(dir.join("src/lib.rs").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use solution::Error;
+
+use crate::fetch::Client;
+use crate::html;
+
+/// Verdict of the site on a submitted answer
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    /// Submitted too soon after a previous answer, with the seconds left to wait
+    Wait(u64),
+    AlreadySolved,
+    /// Any other response, holding the text of its message
+    Unknown(String),
+}
+
+impl Outcome {
+    /**
+        Reads the outcome from the text of the <article> in the HTML page returned on submission,
+        e.g. "That's not the right answer; your answer is too high." or "You have 1m 5s left to wait."
+    */
+    pub fn parse(html: &str) -> Self {
+        let message = article_text(html);
+        if message.contains("That's the right answer") {
+            Outcome::Correct
+        } else if message.contains("your answer is too high") {
+            Outcome::TooHigh
+        } else if message.contains("your answer is too low") {
+            Outcome::TooLow
+        } else if message.contains("That's not the right answer") {
+            Outcome::Wrong
+        } else if let Some(seconds) = seconds_left_to_wait(&message) {
+            Outcome::Wait(seconds)
+        } else if message.contains("Did you already complete it") {
+            Outcome::AlreadySolved
+        } else {
+            Outcome::Unknown(message)
+        }
+    }
+
+    /// Whether the answer is known to be wrong, and thus never worth submitting again
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::TooHigh | Outcome::TooLow | Outcome::Wrong)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "correct"),
+            Outcome::TooHigh => write!(f, "wrong, too high"),
+            Outcome::TooLow => write!(f, "wrong, too low"),
+            Outcome::Wrong => write!(f, "wrong"),
+            Outcome::Wait(seconds) => write!(f, "submitted too recently, wait {}s", seconds),
+            Outcome::AlreadySolved => write!(f, "already solved"),
+            Outcome::Unknown(message) => write!(f, "unknown response: {}", message),
+        }
+    }
+}
+
+/// Text of the first <article> element, or of the whole page, without tags and extra whitespace
+fn article_text(html: &str) -> String {
+    let article = html::elements(html, "article").first().copied().unwrap_or(html);
+    html::text(article).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parses "You have 1m 5s left to wait" into 65
+fn seconds_left_to_wait(message: &str) -> Option<u64> {
+    let start = message.find("You have ")? + "You have ".len();
+    let end = start + message[start..].find(" left to wait")?;
+    message[start..end].split(' ').try_fold(0, |seconds, amount| {
+        let (value, unit) = amount.split_at(amount.find(|c: char| !c.is_ascii_digit())?);
+        let value: u64 = value.parse().ok()?;
+        match unit {
+            "h" => Some(seconds + value * 3600),
+            "m" => Some(seconds + value * 60),
+            "s" => Some(seconds + value),
+            _ => None,
+        }
+    })
+}
+
+/// Seconds the site asks to wait before submitting again after a wrong answer
+const WRONG_ANSWER_COOLDOWN: u64 = 60;
+
+/// Seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// One answer submitted to the site and the outcome it got
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Submission {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub outcome: Outcome,
+    /// Seconds since the Unix epoch
+    pub time: u64,
+}
+
+/**
+    Every answer submitted for a year, stored in submissions/<year>.toml below the cache directory,
+    so that known wrong answers are never submitted twice and the site's cooldowns are respected.
+*/
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Submissions {
+    #[serde(default, rename = "submission")]
+    submissions: Vec<Submission>,
+}
+
+impl Submissions {
+    /// Location of the submissions log of a year below the cache directory of the configuration
+    pub fn path(cache_dir: &Path, year: u16) -> PathBuf {
+        cache_dir.join("submissions").join(format!("{}.toml", year))
+    }
+
+    /// Reads a submissions log. A missing file simply holds no submissions.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err).into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(format!("{}: {}", path.display(), err).into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let text = toml::to_string(self)?;
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, text))
+            .map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    pub fn record(&mut self, day: u8, part: u8, answer: &str, outcome: Outcome) {
+        self.submissions.push(Submission { day, part, answer: answer.to_string(), outcome, time: now() });
+    }
+
+    /// Seconds left before the site accepts another answer, if any
+    pub fn seconds_to_wait(&self) -> Option<u64> {
+        self.seconds_to_wait_at(now())
+    }
+
+    /**
+        Seconds left at time now, in seconds since the Unix epoch, before the site accepts another
+        answer: the wait it asked for, or a minute after a wrong answer. The site limits answers
+        per account rather than per day, so every submission of the year counts.
+    */
+    fn seconds_to_wait_at(&self, now: u64) -> Option<u64> {
+        self.submissions
+            .iter()
+            .filter_map(|submission| match submission.outcome {
+                Outcome::Wait(seconds) => Some(submission.time.saturating_add(seconds)),
+                ref outcome if outcome.is_wrong() => Some(submission.time.saturating_add(WRONG_ANSWER_COOLDOWN)),
+                _ => None,
+            })
+            .max()
+            .filter(|&end| end > now)
+            .map(|end| end - now)
+    }
+
+    /**
+        Reason not to submit answer, if any: the part is already solved, the very same answer was
+        wrong, or a numeric answer lies beyond an answer known to be too high or too low.
+    */
+    pub fn known_outcome(&self, day: u8, part: u8, answer: &str) -> Option<&Submission> {
+        let number = answer.parse::<i64>().ok();
+        self.submissions
+            .iter()
+            .filter(|submission| submission.day == day && submission.part == part)
+            .find(|submission| {
+                let guess = submission.answer.parse::<i64>().ok();
+                match (&submission.outcome, number.zip(guess)) {
+                    (Outcome::Correct | Outcome::AlreadySolved, _) => true,
+                    (Outcome::TooHigh, Some((number, guess))) => number >= guess,
+                    (Outcome::TooLow, Some((number, guess))) => number <= guess,
+                    (outcome, _) => outcome.is_wrong() && submission.answer == answer,
+                }
+            })
+    }
+}
+
+impl Client {
+    /// Posts the answer to a part of a day and returns the site's verdict
+    pub fn submit(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<Outcome, Error> {
+        let level = part.to_string();
+        let html = self.send(&format!("{}/day/{}/answer", year, day), Some(&[("level", &level), ("answer", answer)]))?;
+        Ok(Outcome::parse(&html))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::{serve_once, temp_dir};
+    use crate::fetch::Throttle;
+    use std::time::Duration;
+
+    fn page(message: &str) -> String {
+        format!("<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>", message)
+    }
+
+    #[test]
+    fn test_outcome_parse() {
+        assert_eq!(Outcome::parse(&page("That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer.")),
+                   Outcome::Correct);
+        assert_eq!(Outcome::parse(&page("That's not the right answer; your answer is too high.  If you're stuck, <a href=\"/2022/about\">ask</a>. Please wait one minute before trying again.")),
+                   Outcome::TooHigh);
+        assert_eq!(Outcome::parse(&page("That's not the right answer; your answer is too low.")), Outcome::TooLow);
+        assert_eq!(Outcome::parse(&page("That's not the right answer.  If you're stuck...")), Outcome::Wrong);
+        assert_eq!(Outcome::parse(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait. <a href=\"/2022/day/5\">[Return to Day 5]</a>")),
+                   Outcome::Wait(65));
+        assert_eq!(Outcome::parse(&page("You don't seem to be solving the right level.  Did you already complete it? <a href=\"/2022/day/5\">[Return to Day 5]</a>")),
+                   Outcome::AlreadySolved);
+        assert_eq!(Outcome::parse(&page("Something <em>new</em>")), Outcome::Unknown("Something new".to_string()));
+    }
+
+    #[test]
+    fn test_known_outcome() {
+        let mut submissions = Submissions::default();
+        submissions.record(5, 1, "MCD", Outcome::Wrong);
+        submissions.record(6, 1, "100", Outcome::TooHigh);
+        submissions.record(6, 1, "10", Outcome::TooLow);
+        submissions.record(6, 2, "42", Outcome::Wait(30));
+        assert!(submissions.known_outcome(5, 1, "MCD").is_some());
+        assert!(submissions.known_outcome(5, 1, "CMZ").is_none());
+        assert!(submissions.known_outcome(5, 2, "MCD").is_none());
+        assert_eq!(submissions.known_outcome(6, 1, "150").map(|submission| &submission.outcome), Some(&Outcome::TooHigh));
+        assert_eq!(submissions.known_outcome(6, 1, "10").map(|submission| &submission.outcome), Some(&Outcome::TooLow));
+        assert!(submissions.known_outcome(6, 1, "50").is_none());
+        // Only refused by seconds_to_wait, since the answer itself may still be right
+        assert!(submissions.known_outcome(6, 2, "42").is_none());
+        submissions.record(6, 1, "50", Outcome::Correct);
+        assert!(submissions.known_outcome(6, 1, "51").is_some());
+    }
+
+    #[test]
+    fn test_seconds_to_wait() {
+        let submission = |outcome, time| Submission { day: 6, part: 1, answer: "42".to_string(), outcome, time };
+        let mut submissions = Submissions::default();
+        assert_eq!(submissions.seconds_to_wait_at(1000), None);
+        submissions.submissions.push(submission(Outcome::Wait(30), 1000));
+        assert_eq!(submissions.seconds_to_wait_at(1000), Some(30));
+        assert_eq!(submissions.seconds_to_wait_at(1029), Some(1));
+        assert_eq!(submissions.seconds_to_wait_at(1030), None);
+        submissions.submissions.push(submission(Outcome::TooLow, 1010));
+        assert_eq!(submissions.seconds_to_wait_at(1030), Some(40));
+        assert_eq!(submissions.seconds_to_wait_at(1070), None);
+        submissions.submissions.push(submission(Outcome::Correct, 1100));
+        assert_eq!(submissions.seconds_to_wait_at(1100), None);
+        submissions.record(7, 2, "13", Outcome::Wrong);
+        assert!(matches!(submissions.seconds_to_wait(), Some(1..=60)));
+    }
+
+    #[test]
+    fn test_submissions_round_trip() {
+        let dir = temp_dir("submissions");
+        let path = Submissions::path(&dir, 2022);
+        assert_eq!(path, dir.join("submissions").join("2022.toml"));
+        assert!(Submissions::load(&path).unwrap().submissions.is_empty());
+        let mut submissions = Submissions::default();
+        submissions.record(10, 1, "13140", Outcome::Wait(5));
+        submissions.record(10, 1, "13141", Outcome::TooHigh);
+        submissions.save(&path).unwrap();
+        assert_eq!(Submissions::load(&path).unwrap().submissions, submissions.submissions);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_submit() {
+        let dir = temp_dir("submit");
+        let (base_url, requests) = serve_once("200 OK", "<article><p>That's the right answer!</p></article>");
+        let client = Client::new(&base_url, Some("abc".to_string()), Throttle::new(dir.join("stamp"), Duration::ZERO));
+        assert_eq!(client.submit(2022, 5, 2, "MCD").unwrap(), Outcome::Correct);
+        let request = requests.recv().unwrap();
+        assert_eq!(request[0], "POST /2022/day/5/answer HTTP/1.1");
+        assert_eq!(request.last().unwrap(), "level=2&answer=MCD");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::Mutex;
+
+use solution::Error;
+use tracing_subscriber::EnvFilter;
+
+/**
+    Filter directives for a verbosity, the number of -v flags: warnings only by default, then the
+    days' debug events (instructions, moves, directories) with -v, and their trace events (cycles,
+    knots, listed files) with -vv. directives, such as `aoc_2022_day09=trace`, are appended so that
+    they override the verbosity for the days or modules they name.
+*/
+pub fn directives(verbosity: u8, directives: Option<&str>) -> String {
+    let level = match verbosity {
+        0 => "warn",
+        1 => "debug",
+        _ => "trace",
+    };
+    match directives {
+        Some(directives) if !directives.is_empty() => format!("{},{}", level, directives),
+        _ => level.to_string(),
+    }
+}
+
+/**
+    Installs the global subscriber printing the events enabled by directives to stderr, or writing
+    them as JSON lines to file instead when given, one object per event along with its spans.
+*/
+pub fn init(directives: &str, file: Option<&Path>) -> Result<(), Error> {
+    let filter = EnvFilter::try_new(directives).map_err(|err| format!("invalid trace filter `{}`: {}", directives, err))?;
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match file {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| format!("cannot create trace file {}: {}", path.display(), err))?;
+            builder.json().with_current_span(true).with_span_list(true).with_writer(Mutex::new(file)).try_init()?;
+        }
+        None => builder.with_ansi(io::stderr().is_terminal()).with_writer(io::stderr).try_init()?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(directives(0, None), "warn");
+        assert_eq!(directives(1, Some("")), "debug");
+        assert_eq!(directives(3, None), "trace");
+        assert_eq!(directives(0, Some("aoc_2022_day09=trace")), "warn,aoc_2022_day09=trace");
+    }
+
+    #[test]
+    fn test_init_rejects_invalid_filter() {
+        let err = init("aoc_2022_day09=loud", None).unwrap_err();
+        assert!(err.to_string().starts_with("invalid trace filter `aoc_2022_day09=loud`"), "{}", err);
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use solution::{Answer, Error};
+
+use crate::answers;
+use crate::report::Record;
+
+/**
+    Modification times of every file below some directories. Comparing snapshots taken every few
+    hundred milliseconds is plenty for a handful of source and input files, and needs no platform
+    specific notification API.
+*/
+#[derive(PartialEq, Eq, Default, Debug)]
+pub struct Snapshot {
+    files: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl Snapshot {
+    /// Walks dirs recursively, skipping those that don't exist
+    pub fn take(dirs: &[PathBuf]) -> Self {
+        let mut files = BTreeMap::new();
+        let mut pending: Vec<PathBuf> = dirs.to_vec();
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                match entry.metadata() {
+                    Ok(metadata) if metadata.is_dir() => pending.push(path),
+                    Ok(metadata) => {
+                        files.insert(path, metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+        Self { files }
+    }
+
+    /// Files added, modified or removed in newer
+    pub fn changes<'a>(&'a self, newer: &'a Snapshot) -> Vec<&'a Path> {
+        let modified = newer.files.iter().filter(|(path, time)| self.files.get(*path) != Some(time));
+        let removed = self.files.iter().filter(|(path, _)| !newer.files.contains_key(*path));
+        modified.chain(removed).map(|(path, _)| path.as_path()).collect()
+    }
+}
+
+/// Outcome of a cargo command run by the watcher, with its output
+pub struct Outcome {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs cargo with args in the workspace at root, the cargo running aoc if any
+pub fn cargo(root: &Path, args: &[&str]) -> Result<Outcome, Error> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo).args(args).current_dir(root).output()?;
+    Ok(Outcome {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Records of a day printed by aoc run --format json
+pub fn parse_records(json: &str) -> Result<Vec<Record>, Error> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/**
+    Describes the new result of a part: its answer or error, whether it changed since the previous
+    run, and whether it matches the expected answer. Multi-line answers are compared line by line.
+*/
+pub fn describe(record: &Record, previous: Option<&Record>, expected: Option<&Answer>) -> Vec<String> {
+    let part = record.part.map_or("Error".to_string(), |part| format!("Part {}", part));
+    let Some(answer) = &record.answer else {
+        return vec![format!("{}: FAILED, {}", part, record.error.as_deref().unwrap_or_default())];
+    };
+    let mut lines = if answer.contains('\n') {
+        [format!("{}:", part)].into_iter().chain(answer.lines().map(String::from)).collect()
+    } else {
+        vec![format!("{}: {}", part, answer)]
+    };
+    match previous.and_then(|previous| previous.answer.as_deref()) {
+        Some(previous) if previous == answer => {}
+        Some(previous) if !previous.contains('\n') && !answer.contains('\n') => lines.push(format!("    changed, was {}", previous)),
+        Some(previous) => {
+            lines.push("    changed:".to_string());
+            lines.extend(answers::diff(previous, answer).into_iter().map(|line| format!("    {}", line)));
+        }
+        None => {}
+    }
+    match expected.map(Answer::to_string) {
+        Some(expected) if expected.trim_end() == answer.trim_end() => lines.push("    matches the expected answer".to_string()),
+        Some(expected) if !expected.contains('\n') && !answer.contains('\n') => lines.push(format!("    MISMATCH, expected {}", expected)),
+        Some(expected) => {
+            lines.push("    MISMATCH with the expected answer:".to_string());
+            lines.extend(answers::diff(&expected, answer).into_iter().map(|line| format!("    {}", line)));
+        }
+        None => {}
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fetch::test::temp_dir;
+    use std::fs::File;
+    use std::time::Duration;
+
+    fn record(part: u8, answer: &str) -> Record {
+        Record {
+            year: 2022,
+            day: 10,
+            part: Some(part),
+            answer: Some(answer.to_string()),
+            duration_ns: Some(1),
+            input: "-".to_string(),
+            error: None,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let dir = temp_dir("watch");
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "").unwrap();
+        fs::write(dir.join("src/nested/mod.rs"), "").unwrap();
+        let dirs = [dir.join("src"), dir.join("resources")];
+        let before = Snapshot::take(&dirs);
+        assert_eq!(before.files.len(), 2);
+        assert!(before.changes(&Snapshot::take(&dirs)).is_empty());
+
+        let later = SystemTime::now() + Duration::from_secs(10);
+        File::options().write(true).open(dir.join("src/lib.rs")).unwrap().set_modified(later).unwrap();
+        fs::remove_file(dir.join("src/nested/mod.rs")).unwrap();
+        fs::create_dir_all(dir.join("resources/input")).unwrap();
+        fs::write(dir.join("resources/input/first_part.txt"), "noop\n").unwrap();
+        let after = Snapshot::take(&dirs);
+        let mut changes = before.changes(&after);
+        changes.sort();
+        assert_eq!(changes, vec![
+            dir.join("resources/input/first_part.txt").as_path(),
+            dir.join("src/lib.rs").as_path(),
+            dir.join("src/nested/mod.rs").as_path(),
+        ]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_describe() {
+        let expected = Answer::Number(42);
+        assert_eq!(describe(&record(1, "42"), None, Some(&expected)), vec!["Part 1: 42", "    matches the expected answer"]);
+        assert_eq!(describe(&record(1, "41"), Some(&record(1, "42")), Some(&expected)),
+                   vec!["Part 1: 41", "    changed, was 42", "    MISMATCH, expected 42"]);
+        assert_eq!(describe(&record(2, "#.\n.#\n"), Some(&record(2, "#.\n##\n")), None),
+                   vec!["Part 2:", "#.", ".#", "    changed:", "      2 - ##", "      2 + .#"]);
+        let failure = Record { answer: None, error: Some("panicked: boom".to_string()), ..record(2, "") };
+        assert_eq!(describe(&failure, None, Some(&expected)), vec!["Part 2: FAILED, panicked: boom"]);
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let records = vec![record(1, "13140"), record(2, "#.\n")];
+        let json = serde_json::to_string_pretty(&records).unwrap();
+        assert_eq!(parse_records(&json).unwrap(), records);
+        assert!(parse_records("error: could not compile").is_err());
+    }
+}
@@ -0,0 +1,3 @@
+#![no_main]
+
+libfuzzer_sys::fuzz_target!(|bytes: &[u8]| solution::fuzz::<aoc_2022_day01::CalorieCounting>(bytes));
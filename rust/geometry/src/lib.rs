@@ -0,0 +1,449 @@
+use std::fmt;
+use std::iter;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/**
+    Signed integer usable as a coordinate. Distances, directions and lines need a zero, a one, abs()
+    and signum(), which the standard library only provides as inherent methods.
+*/
+pub trait Coordinate: Copy + Ord + Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Neg<Output=Self> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+
+    fn signum(self) -> Self;
+}
+
+macro_rules! coordinate {
+    ($($type:ty),*) => {
+        $(
+            impl Coordinate for $type {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn abs(self) -> Self {
+                    <$type>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$type>::signum(self)
+                }
+            }
+        )*
+    };
+}
+
+coordinate!(i8, i16, i32, i64, i128, isize);
+
+/// Position on a plane, e.g. a knot of a rope
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// Difference between two points, e.g. a step towards some direction
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+pub struct Vector<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// Position in space
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Difference between two points in space
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+pub struct Vector3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Vector<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> Vector3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/**
+    Arithmetic shared by 2D and 3D points and vectors: points move by vectors, the difference of two
+    points is a vector, vectors add up and scale. Distances are measured on vectors and between
+    points.
+*/
+macro_rules! point_and_vector {
+    ($point:ident, $vector:ident, $($field:ident),+) => {
+        impl<T: Coordinate> $point<T> {
+            pub const ORIGIN: Self = Self { $($field: T::ZERO),+ };
+
+            /// Sum of the distances along each axis, i.e. the number of orthogonal steps to other
+            pub fn manhattan(self, other: Self) -> T {
+                (other - self).manhattan()
+            }
+
+            /// Largest distance along an axis, i.e. the number of king moves to other
+            pub fn chebyshev(self, other: Self) -> T {
+                (other - self).chebyshev()
+            }
+        }
+
+        impl<T: Coordinate> $vector<T> {
+            pub const ZERO: Self = Self { $($field: T::ZERO),+ };
+
+            /// Vector whose components are -1, 0 or 1, giving a single step towards the same side
+            pub fn signum(self) -> Self {
+                Self { $($field: self.$field.signum()),+ }
+            }
+
+            pub fn manhattan(self) -> T {
+                T::ZERO $(+ self.$field.abs())+
+            }
+
+            pub fn chebyshev(self) -> T {
+                T::ZERO $(.max(self.$field.abs()))+
+            }
+        }
+
+        impl<T: Add<Output=T>> Add<$vector<T>> for $point<T> {
+            type Output = Self;
+
+            fn add(self, other: $vector<T>) -> Self {
+                Self { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl<T: Sub<Output=T>> Sub<$vector<T>> for $point<T> {
+            type Output = Self;
+
+            fn sub(self, other: $vector<T>) -> Self {
+                Self { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl<T: Sub<Output=T>> Sub for $point<T> {
+            type Output = $vector<T>;
+
+            fn sub(self, other: Self) -> $vector<T> {
+                $vector { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign<$vector<T>> for $point<T> {
+            fn add_assign(&mut self, other: $vector<T>) {
+                $(self.$field += other.$field;)+
+            }
+        }
+
+        impl<T: SubAssign> SubAssign<$vector<T>> for $point<T> {
+            fn sub_assign(&mut self, other: $vector<T>) {
+                $(self.$field -= other.$field;)+
+            }
+        }
+
+        impl<T: Add<Output=T>> Add for $vector<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl<T: Sub<Output=T>> Sub for $vector<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $vector<T> {
+            fn add_assign(&mut self, other: Self) {
+                $(self.$field += other.$field;)+
+            }
+        }
+
+        impl<T: Neg<Output=T>> Neg for $vector<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: Mul<Output=T> + Copy> Mul<T> for $vector<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                Self { $($field: self.$field * factor),+ }
+            }
+        }
+    };
+}
+
+point_and_vector!(Point, Vector, x, y);
+point_and_vector!(Point3, Vector3, x, y, z);
+
+impl<T: Coordinate> Point<T> {
+    /**
+        Points of the straight line from self to other, both included, one per step along the
+        longest axis (Bresenham's algorithm). Horizontal, vertical and 45° lines go through exactly
+        the points one would expect.
+    */
+    pub fn line_to(self, other: Self) -> impl Iterator<Item=Self> {
+        let distance = Vector::new((other.x - self.x).abs(), -(other.y - self.y).abs());
+        let step = (other - self).signum();
+        let mut error = distance.x + distance.y;
+        let mut next = Some(self);
+        iter::from_fn(move || {
+            let point = next?;
+            next = (point != other).then(|| {
+                let mut moved = point;
+                let doubled = error + error;
+                if doubled >= distance.y {
+                    error = error + distance.y;
+                    moved.x = moved.x + step.x;
+                }
+                if doubled <= distance.x {
+                    error = error + distance.x;
+                    moved.y = moved.y + step.y;
+                }
+                moved
+            });
+            Some(point)
+        })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/**
+    The 4 orthogonal directions. y grows downwards, as rows do in puzzle inputs and on screens, so
+    up is a step towards y - 1.
+*/
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Every direction, clockwise from up
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    /// A step in this direction
+    pub fn vector<T: Coordinate>(self) -> Vector<T> {
+        Direction8::from(self).vector()
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.opposite().turn_right()
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn_right().turn_right()
+    }
+}
+
+/// The 8 directions to the cells around a cell, including diagonals. y grows downwards too.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// Every direction, clockwise from up
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up, Direction8::UpRight, Direction8::Right, Direction8::DownRight,
+        Direction8::Down, Direction8::DownLeft, Direction8::Left, Direction8::UpLeft,
+    ];
+
+    /// A step in this direction, diagonal ones moving along both axes
+    pub fn vector<T: Coordinate>(self) -> Vector<T> {
+        let (zero, one) = (T::ZERO, T::ONE);
+        match self {
+            Direction8::Up => Vector::new(zero, -one),
+            Direction8::UpRight => Vector::new(one, -one),
+            Direction8::Right => Vector::new(one, zero),
+            Direction8::DownRight => Vector::new(one, one),
+            Direction8::Down => Vector::new(zero, one),
+            Direction8::DownLeft => Vector::new(-one, one),
+            Direction8::Left => Vector::new(-one, zero),
+            Direction8::UpLeft => Vector::new(-one, -one),
+        }
+    }
+
+    /// Direction an eighth of a turn clockwise
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Direction an eighth of a turn counterclockwise
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Direction8::Up,
+            Direction::Right => Direction8::Right,
+            Direction::Down => Direction8::Down,
+            Direction::Left => Direction8::Left,
+        }
+    }
+}
+
+/// Smallest rectangle holding some points, min and max included
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Bounds<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Coordinate> Bounds<T> {
+    /// Bounds of a single point
+    pub fn new(point: Point<T>) -> Self {
+        Self { min: point, max: point }
+    }
+
+    /// Bounds of every point, None if there are none
+    pub fn of(points: impl IntoIterator<Item=Point<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = Self::new(points.next()?);
+        Some(points.fold(first, Self::including))
+    }
+
+    /// Bounds grown to hold point
+    pub fn including(self, point: Point<T>) -> Self {
+        Self {
+            min: Point::new(self.min.x.min(point.x), self.min.y.min(point.y)),
+            max: Point::new(self.max.x.max(point.x), self.max.y.max(point.y)),
+        }
+    }
+
+    pub fn contains(&self, point: Point<T>) -> bool {
+        self.min.x <= point.x && point.x <= self.max.x && self.min.y <= point.y && point.y <= self.max.y
+    }
+
+    /// Number of columns
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::ONE
+    }
+
+    /// Number of rows
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::ONE
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let mut point = Point::new(3, -2);
+        point += Vector::new(1, 1) * 2;
+        assert_eq!(point, Point::new(5, 0));
+        assert_eq!(point - Point::new(1, 4), Vector::new(4, -4));
+        assert_eq!(point - -Vector::new(1, 2), Point::new(6, 2));
+        assert_eq!(Point3::new(1, 2, 3) + Vector3::new(1, 1, 1) - Point3::ORIGIN, Vector3::new(2, 3, 4));
+        assert_eq!(Vector::new(-7i64, 0).signum(), Vector::new(-1, 0));
+    }
+
+    #[test]
+    fn test_distances() {
+        assert_eq!(Point::new(1, 1).manhattan(Point::new(4, -3)), 7);
+        assert_eq!(Point::new(1, 1).chebyshev(Point::new(4, -3)), 4);
+        assert_eq!(Point3::new(1, 1, 1).manhattan(Point3::ORIGIN), 3);
+        assert_eq!(Vector3::new(0, -5, 2).chebyshev(), 5);
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction::Up.vector(), Vector::new(0, -1));
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert_eq!(Direction8::UpLeft.turn_right(), Direction8::Up);
+        assert_eq!(Direction8::Up.turn_left(), Direction8::UpLeft);
+        assert_eq!(Direction8::DownRight.opposite(), Direction8::UpLeft);
+        assert_eq!(Direction8::ALL.iter().map(|direction| direction.vector::<i32>()).fold(Vector::ZERO, |sum, step| sum + step), Vector::ZERO);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds = Bounds::of([Point::new(2, 3), Point::new(-1, 5), Point::new(0, 4)]).unwrap();
+        assert_eq!(bounds, Bounds { min: Point::new(-1, 3), max: Point::new(2, 5) });
+        assert_eq!((bounds.width(), bounds.height()), (4, 3));
+        assert!(bounds.contains(Point::new(0, 3)) && !bounds.contains(Point::new(0, 6)));
+        assert_eq!(Bounds::<i32>::of([]), None);
+    }
+
+    #[test]
+    fn test_line_to() {
+        let line = |from: Point<i32>, to| from.line_to(to).collect::<Vec<_>>();
+        assert_eq!(line(Point::new(0, 0), Point::new(3, 0)), vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0), Point::new(3, 0)]);
+        assert_eq!(line(Point::new(2, 2), Point::new(0, 0)), vec![Point::new(2, 2), Point::new(1, 1), Point::new(0, 0)]);
+        assert_eq!(line(Point::new(0, 0), Point::new(4, 2)),
+                   vec![Point::new(0, 0), Point::new(1, 1), Point::new(2, 1), Point::new(3, 2), Point::new(4, 2)]);
+        assert_eq!(line(Point::new(5, 5), Point::new(5, 5)), vec![Point::new(5, 5)]);
+    }
+}
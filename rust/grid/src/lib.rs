@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+use std::{error, fmt};
+
+use geometry::{Bounds, Direction8, Point, Vector};
+
+pub use geometry::Direction;
+
+/// Column and row of a cell in a Grid, starting at the top left corner
+pub type Position = (usize, usize);
+
+/// Errors found while parsing a grid from lines of characters. Lines and columns start at 1.
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    Empty,
+    InvalidCell { line: usize, column: usize, text: String },
+    InconsistentRowLength { line: usize, column: usize, text: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty =>
+                write!(f, "expected at least one row of cells"),
+            ParseError::InvalidCell { line, column, text } =>
+                write!(f, "line {}, column {}: unexpected cell `{}`", line, column, text),
+            ParseError::InconsistentRowLength { line, column, text } =>
+                write!(f, "line {}, column {}: expected as many cells as the first row, found `{}`", line, column, text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/**
+    Rectangle of cells stored row after row in a single vector, such as a map of tree heights or a
+    screen of pixels. Cells are addressed by (x, y) positions: get() returns None outside of the
+    grid, while indexing panics like slices do.
+*/
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Grid of width by height cells, all holding value
+    pub fn new(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Self { width, height, cells: vec![value; width * height] }
+    }
+
+    /// Grid of width by height cells, each computed from its position, row after row
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Position) -> T) -> Self {
+        let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(&mut cell).collect();
+        Self { width, height, cells }
+    }
+
+    /**
+        Parses one row per line, converting each character with cell, which returns None for
+        invalid characters. Every row must have as many cells as the first, and there must be at
+        least one cell. Rows are checked for invalid cells before their length.
+    */
+    pub fn parse(lines: impl IntoIterator<Item=impl AsRef<str>>, mut cell: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        let mut cells = vec![];
+        let mut width = 0;
+        let mut height = 0;
+        for (index, line) in lines.into_iter().enumerate() {
+            let line = line.as_ref();
+            let length = cells.len();
+            for (column, c) in line.chars().enumerate() {
+                let value = cell(c).ok_or(ParseError::InvalidCell { line: index + 1, column: column + 1, text: c.to_string() })?;
+                cells.push(value);
+            }
+            if index == 0 {
+                width = cells.len();
+            } else if cells.len() - length != width {
+                return Err(ParseError::InconsistentRowLength { line: index + 1, column: 1, text: line.to_string() });
+            }
+            height += 1;
+        }
+        if cells.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        Ok(Self { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.contains(position).then(|| &self.cells[position.1 * self.width + position.0])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.contains(position).then(|| &mut self.cells[position.1 * self.width + position.0])
+    }
+
+    /// Cells of row y, from left to right
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {} out of a grid of height {}", y, self.height);
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Cells of column x, from top to bottom
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        assert!(x < self.width, "column {} out of a grid of width {}", x, self.width);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// Rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// Positions of every cell, row after row
+    pub fn positions(&self) -> impl Iterator<Item=Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, row after row
+    pub fn cells(&self) -> impl Iterator<Item=(Position, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    /// Position next to position in direction, if it's in the grid
+    pub fn step(&self, position: Position, direction: Direction) -> Option<Position> {
+        self.offset(position, direction.vector())
+    }
+
+    fn offset(&self, (x, y): Position, vector: Vector<isize>) -> Option<Position> {
+        let position = (x.checked_add_signed(vector.x)?, y.checked_add_signed(vector.y)?);
+        self.contains(position).then_some(position)
+    }
+
+    /// Cells met going from position towards direction up to the edge of the grid, position excluded
+    pub fn walk(&self, position: Position, direction: Direction) -> impl Iterator<Item=(Position, &T)> {
+        std::iter::successors(self.step(position, direction), move |&position| self.step(position, direction))
+            .map(|position| (position, &self[position]))
+    }
+
+    /// Positions of the up to 4 cells sharing a side with position
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item=Position> + '_ {
+        Direction::ALL.into_iter().filter_map(move |direction| self.step(position, direction))
+    }
+
+    /// Positions of the up to 8 cells sharing a side or a corner with position
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item=Position> + '_ {
+        Direction8::ALL.into_iter().filter_map(move |direction| self.offset(position, direction.vector()))
+    }
+
+    /// Grid of the cells converted by f
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Grid whose rows are the columns of this one
+    pub fn transpose(&self) -> Self where T: Clone {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+
+    /// Grid turned a quarter clockwise, the left column becoming the top row
+    pub fn rotate_clockwise(&self) -> Self where T: Clone {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(y, self.height - 1 - x)].clone())
+    }
+
+    /// Grid turned a quarter counterclockwise, the top row becoming the left column
+    pub fn rotate_counterclockwise(&self) -> Self where T: Clone {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(self.width - 1 - y, x)].clone())
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(position).unwrap_or_else(|| panic!("position {:?} out of a {}x{} grid", position, width, height))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| panic!("position {:?} out of a {}x{} grid", position, width, height))
+    }
+}
+
+/// One line per row, cells written next to each other, e.g. the digits of a height map
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            row.iter().try_for_each(|cell| write!(f, "{}", cell))?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+    Unbounded grid holding only the cells that were set, for puzzles whose cells spread in every
+    direction from a starting point, such as the positions visited by a rope.
+*/
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point<isize>, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self { cells: HashMap::new() }
+    }
+
+    /// Sets the cell at point, returning its previous value
+    pub fn insert(&mut self, point: Point<isize>, value: T) -> Option<T> {
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: Point<isize>) -> Option<T> {
+        self.cells.remove(&point)
+    }
+
+    pub fn get(&self, point: Point<isize>) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point<isize>) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn contains(&self, point: Point<isize>) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    /// Number of cells set
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Cells set, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item=(Point<isize>, &T)> {
+        self.cells.iter().map(|(point, value)| (*point, value))
+    }
+
+    /// Smallest rectangle holding every cell set
+    pub fn bounds(&self) -> Option<Bounds<isize>> {
+        Bounds::of(self.cells.keys().copied())
+    }
+
+    /// The 4 points sharing a side with point, whether set or not
+    pub fn neighbours(point: Point<isize>) -> impl Iterator<Item=Point<isize>> {
+        Direction::ALL.into_iter().map(move |direction| point + direction.vector())
+    }
+
+    /// The 8 points sharing a side or a corner with point, whether set or not
+    pub fn neighbours8(point: Point<isize>) -> impl Iterator<Item=Point<isize>> {
+        Direction8::ALL.into_iter().map(move |direction| point + direction.vector())
+    }
+
+    /// Dense grid covering the bounds, with empty in the cells that aren't set, and its top left corner
+    pub fn to_grid(&self, empty: T) -> Option<(Grid<T>, Point<isize>)> where T: Clone {
+        let bounds = self.bounds()?;
+        let grid = Grid::from_fn(bounds.width() as usize, bounds.height() as usize, |(x, y)| {
+            self.get(bounds.min + Vector::new(x as isize, y as isize)).unwrap_or(&empty).clone()
+        });
+        Some((grid, bounds.min))
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(Point<isize>, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item=(Point<isize>, T)>>(iter: I) -> Self {
+        Self { cells: iter.into_iter().collect() }
+    }
+}
+
+impl<T> Extend<(Point<isize>, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item=(Point<isize>, T)>>(&mut self, iter: I) {
+        self.cells.extend(iter)
+    }
+}
+
+/// The bounds of the cells set, one line per row, with a '.' in the cells that aren't set
+impl<T: fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(bounds) = self.bounds() else { return Ok(()) };
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                match self.get(Point::new(x, y)) {
+                    Some(value) => write!(f, "{}", value)?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn digits(text: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(text.lines(), |c| c.to_digit(10))
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.to_string(), "123\n456\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(digits(""), Err(ParseError::Empty));
+        assert_eq!(digits("\n\n"), Err(ParseError::Empty));
+        assert_eq!(digits("12\n3x\n45"), Err(ParseError::InvalidCell { line: 2, column: 2, text: "x".to_string() }));
+        assert_eq!(digits("12\n345"), Err(ParseError::InconsistentRowLength { line: 2, column: 1, text: "345".to_string() }));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![&[1, 2, 3][..], &[4, 5, 6][..]]);
+        assert_eq!(grid.column(1).collect::<Vec<_>>(), vec![&2, &5]);
+        assert_eq!(grid.cells().nth(4), Some(((1, 1), &5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "position (0, 2) out of a 3x2 grid")]
+    fn test_index_out_of_bounds() {
+        let _ = digits("123\n456").unwrap()[(0, 2)];
+    }
+
+    #[test]
+    fn test_walk() {
+        let grid = digits("123\n456\n789").unwrap();
+        assert_eq!(grid.walk((0, 1), Direction::Right).collect::<Vec<_>>(), vec![((1, 1), &5), ((2, 1), &6)]);
+        assert_eq!(grid.walk((1, 2), Direction::Up).map(|(_, cell)| *cell).collect::<Vec<_>>(), vec![5, 2]);
+        assert_eq!(grid.walk((0, 0), Direction::Left).count(), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, ' ');
+        assert_eq!(grid.neighbours((0, 0)).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((0, 0)).collect::<Vec<_>>(), vec![(1, 0), (1, 1), (0, 1)]);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(SparseGrid::<char>::neighbours(Point::ORIGIN).collect::<Vec<_>>(),
+                   vec![Point::new(0, -1), Point::new(1, 0), Point::new(0, 1), Point::new(-1, 0)]);
+        assert_eq!(SparseGrid::<char>::neighbours8(Point::ORIGIN).count(), 8);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(grid.transpose().to_string(), "14\n25\n36\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "41\n52\n63\n");
+        assert_eq!(grid.rotate_counterclockwise().to_string(), "36\n25\n14\n");
+        assert_eq!(grid.rotate_clockwise().rotate_counterclockwise(), grid);
+        assert_eq!(grid.map(|digit| digit * 2).to_string(), "246\n81012\n");
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut grid: SparseGrid<char> = [(Point::ORIGIN, 's'), (Point::new(2, -1), '#')].into_iter().collect();
+        assert_eq!(grid.bounds(), Some(Bounds { min: Point::new(0, -1), max: Point::new(2, 0) }));
+        assert_eq!(grid.to_string(), "..#\ns..\n");
+        grid.insert(Point::new(-1, 1), '#');
+        let (dense, origin) = grid.to_grid(' ').unwrap();
+        assert_eq!((dense.to_string(), origin), ("   #\n s  \n#   \n".to_string(), Point::new(-1, -1)));
+        assert_eq!(grid.remove(Point::ORIGIN), Some('s'));
+        assert_eq!(grid.len(), 2);
+        assert_eq!(SparseGrid::<char>::new().bounds(), None);
+        assert_eq!(SparseGrid::<char>::new().to_string(), "");
+    }
+}
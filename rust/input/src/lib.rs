@@ -0,0 +1,183 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::iter::Peekable;
+use std::path::Path;
+
+/// File name that reads from the standard input instead of a file
+pub const STDIN: &str = "-";
+
+const BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/**
+    Opens file_name, or the standard input if file_name is "-", skipping a leading UTF-8 byte order
+    mark if there is one. Every other reader in this crate is built on top of this one.
+*/
+pub fn open(file_name: impl AsRef<Path>) -> io::Result<impl BufRead> {
+    let file_name = file_name.as_ref();
+    let mut reader: Box<dyn Read> = if file_name == Path::new(STDIN) {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(file_name)?)
+    };
+
+    // Read up to the size of a BOM. A single read() may return less even if there is more.
+    let mut prefix = Vec::with_capacity(BOM.len());
+    while prefix.len() < BOM.len() {
+        let mut byte = [0];
+        if reader.read(&mut byte)? == 0 {
+            break;
+        }
+        prefix.push(byte[0]);
+    }
+    if prefix == BOM {
+        prefix.clear();
+    }
+    Ok(BufReader::new(Cursor::new(prefix).chain(reader)))
+}
+
+/// Bytes with CRLF line endings translated to LF
+pub fn get_file_bytes(file_name: impl AsRef<Path>) -> io::Result<impl Iterator<Item=io::Result<u8>>> {
+    Ok(Bytes { bytes: open(file_name)?.bytes().peekable() })
+}
+
+/// UTF-8 decoded characters with CRLF line endings translated to LF
+pub fn get_file_chars(file_name: impl AsRef<Path>) -> io::Result<impl Iterator<Item=io::Result<char>>> {
+    Ok(Chars { bytes: get_file_bytes(file_name)?.peekable() })
+}
+
+/// The whole input as a single string, decoded by get_file_chars
+pub fn get_file_string(file_name: impl AsRef<Path>) -> io::Result<String> {
+    get_file_chars(file_name)?.collect()
+}
+
+/// Iterator over bytes that drops the CR of every CRLF pair
+struct Bytes<I: Iterator<Item=io::Result<u8>>> {
+    bytes: Peekable<I>,
+}
+
+impl<I: Iterator<Item=io::Result<u8>>> Iterator for Bytes<I> {
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.bytes.next() {
+            Some(Ok(b'\r')) if matches!(self.bytes.peek(), Some(Ok(b'\n'))) => self.bytes.next(),
+            byte => byte,
+        }
+    }
+}
+
+/**
+    Iterator decoding UTF-8 characters one at a time out of a byte iterator. A byte that can't
+    continue a sequence is left for the next character rather than swallowed by the invalid one.
+*/
+struct Chars<I: Iterator<Item=io::Result<u8>>> {
+    bytes: Peekable<I>,
+}
+
+impl<I: Iterator<Item=io::Result<u8>>> Chars<I> {
+    fn decode(&mut self, first_byte: u8) -> io::Result<char> {
+        let width = match first_byte {
+            0x00..=0x7F => 1,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return Err(invalid_utf8(&[first_byte])),
+        };
+        let mut buffer = vec![first_byte];
+        while buffer.len() < width {
+            // Only continuation bytes, 10xxxxxx, and read errors belong to this character
+            match self.bytes.next_if(|byte| !matches!(byte, Ok(byte) if byte & 0xC0 != 0x80)) {
+                Some(byte) => buffer.push(byte?),
+                None => return Err(invalid_utf8(&buffer)),
+            }
+        }
+        std::str::from_utf8(&buffer)
+            .map_err(|_| invalid_utf8(&buffer))
+            .map(|character| character.chars().next().expect("Decoded exactly one character"))
+    }
+}
+
+impl<I: Iterator<Item=io::Result<u8>>> Iterator for Chars<I> {
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.bytes.next().map(|first_byte| self.decode(first_byte?))
+    }
+}
+
+fn invalid_utf8(bytes: &[u8]) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid UTF-8 sequence {:02X?}", bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn resource(file_name: &str) -> String {
+        format!("{}/resources/test/{}", env!("CARGO_MANIFEST_DIR"), file_name)
+    }
+
+    #[test]
+    fn test_read_characters_from_file() {
+        let mut char_iter = get_file_chars(resource("characters.txt")).expect("File exists for testing");
+
+        assert_eq!(char_iter.next().transpose().unwrap(), Some('f'));
+        assert_eq!(char_iter.next().transpose().unwrap(), Some('i'));
+        assert_eq!(char_iter.next().transpose().unwrap(), Some('r'));
+    }
+
+    #[test]
+    fn test_read_characters_with_bom_and_crlf() {
+        let string = get_file_chars(resource("bom_crlf.txt"))
+            .expect("File exists for testing")
+            .collect::<io::Result<String>>()
+            .unwrap();
+
+        assert_eq!(string, "first line\nsecond line\n\nthird line\n");
+    }
+
+    #[test]
+    fn test_read_characters_invalid_utf8() {
+        let chars = get_file_chars(resource("invalid_utf8.txt"))
+            .expect("File exists for testing")
+            .collect::<Vec<io::Result<char>>>();
+
+        assert_eq!(chars[0].as_ref().unwrap(), &'a');
+        assert_eq!(chars[2].as_ref().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(chars[4].as_ref().unwrap(), &'b');
+    }
+
+    #[test]
+    fn test_decode_keeps_byte_after_invalid_lead_byte() {
+        let mut chars = Chars { bytes: b"\xC3A".iter().map(|byte| Ok(*byte)).peekable() };
+
+        assert_eq!(chars.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(chars.next().transpose().unwrap(), Some('A'));
+        assert!(chars.next().is_none());
+    }
+
+    #[test]
+    fn test_read_bytes_with_bom_and_crlf() {
+        let bytes = get_file_bytes(resource("bom_crlf.txt"))
+            .expect("File exists for testing")
+            .collect::<io::Result<Vec<u8>>>()
+            .unwrap();
+
+        assert_eq!(bytes, b"first line\nsecond line\n\nthird line\n");
+    }
+
+    #[test]
+    fn test_read_string_with_bom_and_crlf() {
+        assert_eq!(get_file_string(resource("bom_crlf.txt")).unwrap(), "first line\nsecond line\n\nthird line\n");
+    }
+
+    #[test]
+    fn test_read_string_invalid_utf8() {
+        assert_eq!(get_file_string(resource("invalid_utf8.txt")).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_open_missing_file() {
+        assert!(get_file_string(resource("missing.txt")).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use std::str::FromStr;
+
+/**
+    Returns the 1-based column, in characters, at which token starts inside line. token is expected
+    to be a subslice of line, as returned by trim(), split() and the like. Otherwise returns 1.
+*/
+pub fn column_of(line: &str, token: &str) -> usize {
+    let offset = (token.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    line.get(..offset).map_or(1, |before| before.chars().count() + 1)
+}
+
+/**
+    Slice of an input line that remembers where it comes from, so that whatever fails to parse can
+    be reported with its line, column and text. Parsers taking a token apart return the tokens they
+    found, and the offending token as their error, which days turn into their own ParseError
+    variants with error() or the error_at! macro.
+*/
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Token<'a> {
+    line: usize,
+    raw_line: &'a str,
+    text: &'a str,
+}
+
+/**
+    Closure turning a Token into a day's ParseError variant, e.g.
+    `line.split_once(",").map_err(error_at!(ParseError::MissingComma))?`, for the usual variants
+    holding the line, column and text of what was found.
+*/
+#[macro_export]
+macro_rules! error_at {
+    ($($variant:ident)::+) => {
+        |token: $crate::Token| token.error(|line, column, text| $($variant)::+ { line, column, text })
+    };
+}
+
+impl<'a> Token<'a> {
+    /// The whole of raw_line, whose number starts at 1
+    pub fn new(line: usize, raw_line: &'a str) -> Self {
+        Self { line, raw_line, text: raw_line }
+    }
+
+    fn slice(self, text: &'a str) -> Self {
+        Self { text, ..self }
+    }
+
+    pub fn as_str(self) -> &'a str {
+        self.text
+    }
+
+    pub fn line(self) -> usize {
+        self.line
+    }
+
+    /// Column of the first character, starting at 1
+    pub fn column(self) -> usize {
+        column_of(self.raw_line, self.text)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Error built from the position and text of this token
+    pub fn error<E>(self, variant: fn(usize, usize, String) -> E) -> E {
+        variant(self.line, self.column(), self.text.to_string())
+    }
+
+    pub fn trim(self) -> Self {
+        self.slice(self.text.trim())
+    }
+
+    /// Tokens before and after the first separator, or this token if there is none
+    pub fn split_once(self, separator: &str) -> Result<(Self, Self), Self> {
+        let (before, after) = self.text.split_once(separator).ok_or(self)?;
+        Ok((self.slice(before), self.slice(after)))
+    }
+
+    /// Tokens between separators, possibly empty ones like str::split
+    pub fn split(self, separator: &'a str) -> impl Iterator<Item=Self> {
+        self.text.split(separator).map(move |text| self.slice(text))
+    }
+
+    /// Tokens separated by whitespace
+    pub fn words(self) -> impl Iterator<Item=Self> {
+        self.text.split_whitespace().map(move |text| self.slice(text))
+    }
+
+    /// Consecutive tokens of width characters, the last one possibly shorter, e.g. drawn columns
+    pub fn columns(self, width: usize) -> impl Iterator<Item=Self> {
+        assert!(width > 0, "columns must be at least one character wide");
+        let mut rest = self.text;
+        std::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            let end = rest.char_indices().nth(width).map_or(rest.len(), |(index, _)| index);
+            let (column, after) = rest.split_at(end);
+            rest = after;
+            Some(self.slice(column))
+        })
+    }
+
+    /// Items parsed from the tokens between separators, stopping at the first error
+    pub fn list<T, E>(self, separator: &'a str, item: impl FnMut(Self) -> Result<T, E>) -> Result<Vec<T>, E> {
+        self.split(separator).map(item).collect()
+    }
+
+    /// A `<key> <value>` token split at its first space, where neither may be empty
+    pub fn key_value(self) -> Result<(Self, Self), Self> {
+        match self.split_once(" ") {
+            Ok((key, value)) if !key.is_empty() && !value.is_empty() => Ok((key, value)),
+            _ => Err(self),
+        }
+    }
+
+    /// An integer, with an optional sign, as parsed by T::from_str
+    pub fn integer<T: FromStr>(self) -> Result<T, Self> {
+        self.text.parse().map_err(|_| self)
+    }
+
+    /**
+        Matches the whole token against a pattern made of literal keywords and N `{}` placeholders,
+        each matching a single word, e.g. `move {} from {} to {}` or `$ cd {}`. Returns the words
+        in order, or this token if it doesn't match.
+    */
+    pub fn pattern<const N: usize>(self, pattern: &str) -> Result<[Self; N], Self> {
+        let mut literals = pattern.split("{}");
+        let first = literals.next().unwrap_or_default();
+        let mut rest = self.text.strip_prefix(first).ok_or(self)?;
+        let mut words = Vec::with_capacity(N);
+        for literal in literals {
+            let end = if literal.is_empty() { Some(rest.len()) } else { rest.find(literal) };
+            let word = &rest[..end.ok_or(self)?];
+            if word.is_empty() || word.contains(char::is_whitespace) {
+                return Err(self);
+            }
+            words.push(self.slice(word));
+            rest = &rest[word.len() + literal.len()..];
+        }
+        if !rest.is_empty() {
+            return Err(self);
+        }
+        assert_eq!(words.len(), N, "pattern `{}` doesn't have {} placeholders", pattern, N);
+        Ok(words.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+/// Tokens of whole lines, numbered from 1
+pub fn lines<'a, S: AsRef<str> + ?Sized + 'a>(lines: impl IntoIterator<Item=&'a S>) -> impl Iterator<Item=Token<'a>> {
+    lines.into_iter().enumerate().map(|(index, line)| Token::new(index + 1, line.as_ref()))
+}
+
+/**
+    Groups lines into the sections separated by blank lines, like str::split would: consecutive or
+    trailing blank lines give empty sections, and there is always at least one section.
+*/
+pub fn sections<'a>(lines: impl IntoIterator<Item=Token<'a>>) -> Vec<Vec<Token<'a>>> {
+    let mut sections = vec![vec![]];
+    for line in lines {
+        if line.trim().is_empty() {
+            sections.push(vec![]);
+        } else if let Some(section) = sections.last_mut() {
+            section.push(line);
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(PartialEq, Debug)]
+    enum ParseError {
+        Invalid { line: usize, column: usize, text: String },
+    }
+
+    fn token(text: &str) -> Token<'_> {
+        Token::new(4, text)
+    }
+
+    #[test]
+    fn test_column_of() {
+        let line = "  move 1 from 2 to 3";
+        assert_eq!(column_of(line, line.trim()), 3);
+        assert_eq!(column_of(line, line.split(' ').nth(3).unwrap()), 8);
+        assert_eq!(column_of("é x", "é x".split(' ').nth(1).unwrap()), 3);
+        assert_eq!(column_of(line, "move"), 1);
+    }
+
+    #[test]
+    fn test_error_at() {
+        let line = token("  2-4,x");
+        let error = line.trim().split_once(",").and_then(|(_, second)| second.integer::<u8>()).map_err(error_at!(ParseError::Invalid));
+        assert_eq!(error, Err(ParseError::Invalid { line: 4, column: 7, text: "x".to_string() }));
+    }
+
+    #[test]
+    fn test_split() {
+        let (first, second) = token("2-4,6-8").split_once(",").unwrap();
+        assert_eq!((first.as_str(), second.column()), ("2-4", 5));
+        assert_eq!(token("2-4").split_once(","), Err(token("2-4")));
+        assert_eq!(token("a,,b").split(",").map(Token::as_str).collect::<Vec<_>>(), vec!["a", "", "b"]);
+        assert_eq!(token(" a  b ").words().map(Token::column).collect::<Vec<_>>(), vec![2, 5]);
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(token("1,-2,+3").list(",", Token::integer::<i8>), Ok(vec![1, -2, 3]));
+        assert_eq!(token("1,x,3").list(",", Token::integer::<i8>).map_err(Token::column), Err(3));
+        assert_eq!(token("-1").integer::<usize>(), Err(token("-1")));
+    }
+
+    #[test]
+    fn test_key_value() {
+        let (key, value) = token("addx -5").key_value().unwrap();
+        assert_eq!((key.as_str(), value.as_str(), value.column()), ("addx", "-5", 6));
+        assert!(token("R4").key_value().is_err());
+        assert!(token("R ").key_value().is_err());
+    }
+
+    #[test]
+    fn test_pattern() {
+        let line = token("move 10 from 1 to 2");
+        let [n, from, to] = line.pattern("move {} from {} to {}").unwrap();
+        assert_eq!((n.as_str(), from.as_str(), to.column()), ("10", "1", 19));
+        assert_eq!(line.pattern::<3>("move {} from {} {}"), Err(line));
+        assert_eq!(token("move 1 2 from 1 to 2").pattern::<3>("move {} from {} to {}"), Err(token("move 1 2 from 1 to 2")));
+        assert_eq!(token("move 1 from 1 to 2 3").pattern::<3>("move {} from {} to {}").map(|_| ()), Err(token("move 1 from 1 to 2 3")));
+        assert!(token("$ ls").pattern::<0>("$ ls").is_ok());
+        assert!(token("$ cd").pattern::<1>("$ cd {}").is_err());
+        let [size, name] = token("8504156 c.dat").pattern("{} {}").unwrap();
+        assert_eq!((size.integer(), name.as_str()), (Ok(8_504_156), "c.dat"));
+    }
+
+    #[test]
+    fn test_columns() {
+        let columns: Vec<_> = token("[a]     [é]").columns(4).map(|column| (column.as_str(), column.column())).collect();
+        assert_eq!(columns, vec![("[a] ", 1), ("    ", 5), ("[é]", 9)]);
+        assert_eq!(token("").columns(4).count(), 0);
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "1\n2\n\n3\n \n\n";
+        let sections = sections(lines(input.lines()));
+        let texts: Vec<Vec<&str>> = sections.iter().map(|section| section.iter().map(|line| line.as_str()).collect()).collect();
+        assert_eq!(texts, vec![vec!["1", "2"], vec!["3"], vec![], vec![]]);
+        assert_eq!(sections[1][0].line(), 4);
+        assert_eq!(super::sections(lines(Vec::<String>::new().iter())), vec![vec![]]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable which, when set, makes checks write the snapshots instead of comparing them
+pub const UPDATE_VARIABLE: &str = "AOC_UPDATE_SNAPSHOTS";
+
+/**
+    Asserts that a rendering, anything that derefs to a str, matches the snapshot of that name in
+    the calling crate's resources/snapshots directory, e.g.
+    `assert_snapshot!("crt_example", render_crt_output(lines).unwrap())`. Snapshots are created and
+    updated with `cargo run -p aoc -- test --update-snapshots`.
+*/
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $actual:expr) => {
+        if let Err(message) = $crate::check(::std::path::Path::new(env!("CARGO_MANIFEST_DIR")), $name, &$actual) {
+            panic!("{}", message);
+        }
+    };
+}
+
+/// File holding the snapshot name of the crate in dir
+pub fn path(dir: &Path, name: &str) -> PathBuf {
+    dir.join("resources/snapshots").join(format!("{}.txt", name))
+}
+
+/// Compares actual with the snapshot name of the crate in dir, or writes it if UPDATE_VARIABLE is set
+pub fn check(dir: &Path, name: &str, actual: &str) -> Result<(), String> {
+    let update = env::var_os(UPDATE_VARIABLE).is_some_and(|value| !value.is_empty());
+    compare(&path(dir, name), actual, update)
+}
+
+fn compare(path: &Path, actual: &str, update: bool) -> Result<(), String> {
+    if update {
+        return path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, actual))
+            .map_err(|err| format!("cannot write snapshot {}: {}", path.display(), err));
+    }
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(format!(
+            "snapshot {} doesn't exist yet, create it with: cargo run -p aoc -- test --update-snapshots\n{}",
+            path.display(), actual)),
+        Err(err) => return Err(format!("cannot read snapshot {}: {}", path.display(), err)),
+    };
+    if expected == actual {
+        return Ok(());
+    }
+    let mut message = format!("rendering doesn't match snapshot {}, expected (-) and actual (+) lines:\n", path.display());
+    let differences = diff(&expected, actual);
+    if differences.is_empty() {
+        message.push_str("    only the line endings differ\n");
+    }
+    for line in differences {
+        message.push_str(&format!("    {}\n", line));
+    }
+    message.push_str("Accept the new rendering with: cargo run -p aoc -- test --update-snapshots");
+    Err(message)
+}
+
+/**
+    Line by line diff of two texts, listing only the lines that differ, prefixed by their 1-based
+    line number. Expected lines start with '-' and actual lines with '+'.
+*/
+pub fn diff(expected: &str, actual: &str) -> Vec<String> {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let mut differences = Vec::new();
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        let (expected_line, actual_line) = (expected_lines.get(i), actual_lines.get(i));
+        if expected_line != actual_line {
+            if let Some(line) = expected_line {
+                differences.push(format!("{:>3} - {}", i + 1, line));
+            }
+            if let Some(line) = actual_line {
+                differences.push(format!("{:>3} + {}", i + 1, line));
+            }
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff("#.\n.#\n", "#.\n##\n#.\n"), vec!["  2 - .#", "  2 + ##", "  3 + #."]);
+        assert!(diff("a\n", "a").is_empty());
+    }
+
+    #[test]
+    fn test_compare() {
+        let dir = env::temp_dir().join(format!("aoc-snapshot-{}", process::id()));
+        let path = path(&dir, "screen");
+        let missing = compare(&path, "#.\n", false).unwrap_err();
+        assert!(missing.contains("doesn't exist yet") && missing.ends_with("#.\n"));
+
+        compare(&path, "#.\n.#\n", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "#.\n.#\n");
+        assert_eq!(compare(&path, "#.\n.#\n", false), Ok(()));
+        let mismatch = compare(&path, "#.\n##\n", false).unwrap_err();
+        assert!(mismatch.contains("      2 - .#\n      2 + ##\n"), "{}", mismatch);
+        assert!(compare(&path, "#.\n.#", false).unwrap_err().contains("only the line endings differ"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,251 @@
+use std::error;
+use std::fmt;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Type erased error returned by solutions, usually one of the day's own ParseError variants
+pub type Error = Box<dyn error::Error + Send + Sync>;
+
+/**
+    Answer to one part of a puzzle. Most puzzles are answered with a number, but some expect text,
+    such as day 5's top crates or day 10's CRT rendering which spans several lines. Numbers are
+    stored as i128 so that every usize and isize answer converts without wrapping.
+*/
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(n: isize) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+/**
+    Common interface to every day. The input is parsed once into the day's model, and both parts
+    are then computed from that same model. parse() requires Sized so that the remaining methods
+    can still be called through a Box<dyn Solution>.
+*/
+pub trait Solution {
+    /// Parses the whole puzzle input
+    fn parse(input: &str) -> Result<Self, Error> where Self: Sized;
+
+    /// Response to the first part
+    fn part1(&self) -> Result<Answer, Error>;
+
+    /// Response to the second part
+    fn part2(&self) -> Result<Answer, Error>;
+}
+
+/**
+    Parses input into a type erased solution. Meant to be stored as a fn pointer in registries. The
+    solution is Send and Sync, so that both parts can be solved at once on different threads.
+*/
+pub fn parse_boxed<S: Solution + Send + Sync + 'static>(input: &str) -> Result<Box<dyn Solution + Send + Sync>, Error> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+/// Random number generator handed to generators. ChaCha8 yields the same inputs for a seed everywhere.
+pub type Random = ChaCha8Rng;
+
+/// Seeded generator, so that a generated input can be reproduced from its seed
+pub fn random(seed: u64) -> Random {
+    Random::seed_from_u64(seed)
+}
+
+/**
+    Random valid inputs for stress and fuzz testing. size scales the input, e.g. the number of
+    lines or groups, as documented by each day. Generated inputs always parse and both parts can be
+    computed from them.
+*/
+pub trait Generator {
+    fn generate(random: &mut Random, size: usize) -> String;
+}
+
+/**
+    Property tests of a day's parser, meant to be called from its tests: the model parsed from a
+    generated input of any seed and size renders back into an input parsing to the same model, and
+    arbitrary text goes through fuzz() without panicking. Failing cases are kept, and replayed first,
+    in the day's proptest-regressions/lib.txt.
+*/
+#[cfg(feature = "testing")]
+pub fn check_parser<T: Solution + Generator + PartialEq + fmt::Debug + fmt::Display>() {
+    use proptest::prelude::*;
+    use proptest::test_runner::{Config, FileFailurePersistence, TestRunner};
+
+    let config = || Config {
+        failure_persistence: Some(Box::new(FileFailurePersistence::Direct("proptest-regressions/lib.txt"))),
+        ..Config::default()
+    };
+    TestRunner::new(config())
+        .run(&(any::<u64>(), 1..50usize), |(seed, size)| {
+            let parsed = T::parse(&T::generate(&mut random(seed), size)).map_err(|err| TestCaseError::fail(err.to_string()))?;
+            prop_assert_eq!(T::parse(&parsed.to_string()).map_err(|err| TestCaseError::fail(err.to_string()))?, parsed);
+            Ok(())
+        })
+        .unwrap_or_else(|err| panic!("round trip: {}", err));
+    TestRunner::new(config())
+        .run(&any::<String>(), |input| {
+            fuzz::<T>(input.as_bytes());
+            Ok(())
+        })
+        .unwrap_or_else(|err| panic!("parse: {}", err));
+}
+
+/**
+    Fuzzing entry point shared by the days' cargo-fuzz targets. Arbitrary bytes, decoded lossily so
+    that invalid UTF-8 still reaches the parser, may fail to parse or to solve, but must never panic
+    or hang: the parts run on whatever parsed, since some validation only happens while solving.
+*/
+pub fn fuzz<T: Solution>(bytes: &[u8]) {
+    if let Ok(solution) = T::parse(&String::from_utf8_lossy(bytes)) {
+        let _ = solution.part1();
+        let _ = solution.part2();
+    }
+}
+
+/**
+    Interactive session over a day's parsed model, driven by aoc repl. Each line typed is split into
+    whitespace separated words, the first one naming the command and the others its arguments.
+    start() requires Sized for the same reason as Solution::parse().
+*/
+pub trait Repl {
+    /// Parses the whole puzzle input and starts a session on it
+    fn start(input: &str) -> Result<Self, Error> where Self: Sized;
+
+    /// Commands understood by execute, as usage and description pairs listed by help
+    fn commands(&self) -> &'static [(&'static str, &'static str)];
+
+    /// State shown before each command, e.g. the current directory
+    fn prompt(&self) -> String;
+
+    /// Runs a command with its arguments, returning what to print
+    fn execute(&mut self, command: &str, arguments: &[&str]) -> Result<String, Error>;
+}
+
+/// Starts a type erased session. Meant to be stored as a fn pointer in registries.
+pub fn start_boxed<R: Repl + 'static>(input: &str) -> Result<Box<dyn Repl>, Error> {
+    Ok(Box::new(R::start(input)?))
+}
+
+/// Error of a Repl asked for a command missing from its commands
+pub fn unknown_command(command: &str) -> Error {
+    format!("unknown command `{}`, type help for the list of commands", command).into()
+}
+
+/// Parses the optional count of a command such as `step [n]`, 1 when missing
+pub fn count_argument(arguments: &[&str]) -> Result<usize, Error> {
+    match arguments {
+        [] => Ok(1),
+        [count] => count.parse().map_err(|_| format!("expected a number, found `{}`", count).into()),
+        _ => Err(format!("expected at most one argument, found {}", arguments.len()).into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct LineCount(usize);
+
+    impl Solution for LineCount {
+        fn parse(input: &str) -> Result<Self, Error> {
+            Ok(Self(input.lines().count()))
+        }
+
+        fn part1(&self) -> Result<Answer, Error> {
+            Ok(self.0.into())
+        }
+
+        fn part2(&self) -> Result<Answer, Error> {
+            Ok("text".into())
+        }
+    }
+
+    #[test]
+    fn test_parse_boxed() {
+        let solution = parse_boxed::<LineCount>("a\nb\nc").unwrap();
+        assert_eq!(solution.part1().unwrap(), Answer::Number(3));
+        assert_eq!(solution.part2().unwrap(), Answer::Text("text".to_string()));
+    }
+
+    struct Counter(usize);
+
+    impl Repl for Counter {
+        fn start(input: &str) -> Result<Self, Error> {
+            Ok(Self(input.lines().count()))
+        }
+
+        fn commands(&self) -> &'static [(&'static str, &'static str)] {
+            &[("add [n]", "adds n, 1 by default")]
+        }
+
+        fn prompt(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn execute(&mut self, command: &str, arguments: &[&str]) -> Result<String, Error> {
+            match command {
+                "add" => self.0 += count_argument(arguments)?,
+                _ => return Err(unknown_command(command)),
+            }
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_start_boxed() {
+        let mut repl = start_boxed::<Counter>("a\nb").unwrap();
+        repl.execute("add", &[]).unwrap();
+        repl.execute("add", &["3"]).unwrap();
+        assert_eq!(repl.prompt(), "6");
+        assert_eq!(repl.execute("add", &["x"]).unwrap_err().to_string(), "expected a number, found `x`");
+        assert!(repl.execute("sub", &[]).unwrap_err().to_string().starts_with("unknown command `sub`"));
+    }
+
+    #[test]
+    fn test_random_is_reproducible() {
+        use rand::Rng;
+        let numbers = |seed| random(seed).random_iter().take(4).collect::<Vec<u32>>();
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+    }
+
+    #[test]
+    fn test_answer_display() {
+        assert_eq!(Answer::from(-1isize).to_string(), "-1");
+        assert_eq!(Answer::from("CMZ").to_string(), "CMZ");
+        assert_eq!(Answer::from(usize::MAX - 1).to_string(), "18446744073709551614");
+        assert_eq!(Answer::from(isize::MIN).to_string(), "-9223372036854775808");
+    }
+}